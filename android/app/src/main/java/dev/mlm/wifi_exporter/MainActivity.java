@@ -0,0 +1,36 @@
+package dev.mlm.wifi_exporter;
+
+import android.app.NativeActivity;
+import android.content.Intent;
+
+/**
+ * The stock NativeActivity drops onActivityResult and onNewIntent, so the
+ * system file pickers never answer and files opened while we run are lost.
+ * This subclass hands both to the Rust side (android_jni.rs), which picks them
+ * up on the next frame.
+ */
+public class MainActivity extends NativeActivity {
+    static {
+        // NativeActivity loads the library itself, but not through the JVM, so
+        // the native methods below would not resolve without this.
+        System.loadLibrary("wifi_exporter_repose");
+    }
+
+    private native void onActivityResultNative(int requestCode, int resultCode, Intent data);
+
+    private native void onNewIntentNative();
+
+    @Override
+    protected void onActivityResult(int requestCode, int resultCode, Intent data) {
+        super.onActivityResult(requestCode, resultCode, data);
+        onActivityResultNative(requestCode, resultCode, data);
+    }
+
+    /** "Open with" / "Share to" while we're running (launchMode singleTask). */
+    @Override
+    protected void onNewIntent(Intent intent) {
+        super.onNewIntent(intent);
+        setIntent(intent);
+        onNewIntentNative();
+    }
+}
@@ -1,13 +1,99 @@
 #![cfg(target_os = "android")]
 
-use jni::objects::{JObject, JString};
-use jni::{Env, JValue, JavaVM, jni_sig, jni_str};
+use jni::errors::LogErrorAndDefault;
+use jni::objects::{JByteArray, JObject, JObjectArray, JString};
+use jni::refs::LoaderContext;
+use jni::sys::jint;
+use jni::{Env, EnvUnowned, JValue, JavaVM, jni_sig, jni_str};
 use std::ffi::c_void;
-use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
 use winit::platform::android::activity::AndroidApp;
 
 static JAVA_VM: OnceLock<JavaVM> = OnceLock::new();
 
+/// Request code for `ACTION_OPEN_DOCUMENT`.
+pub const REQUEST_OPEN_DOCUMENT: i32 = 0x5701;
+/// Request code for `ACTION_CREATE_DOCUMENT`.
+pub const REQUEST_CREATE_DOCUMENT: i32 = 0x5702;
+/// Request code for the share sheet; its result means the files can go.
+pub const REQUEST_SHARE: i32 = 0x5703;
+
+/// The stock `android.app.NativeActivity` drops `onActivityResult`, so SAF pickers
+/// only work when the APK's activity is this subclass, which forwards results to
+/// `MainActivity.onActivityResultNative` below. It's in `android/`; `cargo apk`
+/// builds can't include Java and keep the stock activity.
+const RESULT_FORWARDING_ACTIVITY: &str = "dev.mlm.wifi_exporter.MainActivity";
+
+const RESULT_OK: i32 = -1;
+
+/// Refuse to pull huge documents into memory; exports are a few KiB.
+const MAX_DOCUMENT_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Debug)]
+pub struct ActivityResult {
+    pub request_code: i32,
+    pub ok: bool,
+    /// Content URIs from `getData()` / `getClipData()`.
+    pub uris: Vec<String>,
+}
+
+static ACTIVITY_RESULTS: Mutex<Vec<ActivityResult>> = Mutex::new(Vec::new());
+
+/// The oldest result not yet handled.
+pub fn next_activity_result() -> Option<ActivityResult> {
+    ACTIVITY_RESULTS
+        .lock()
+        .ok()
+        .and_then(|mut q| (!q.is_empty()).then(|| q.remove(0)))
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_dev_mlm_wifi_1exporter_MainActivity_onActivityResultNative<'caller>(
+    mut unowned_env: EnvUnowned<'caller>,
+    _this: JObject<'caller>,
+    request_code: jint,
+    result_code: jint,
+    data: JObject<'caller>,
+) {
+    unowned_env
+        .with_env(|env| -> jni::errors::Result<()> {
+            let uris = if data.is_null() {
+                Vec::new()
+            } else {
+                intent_uris(env, &data)?
+            };
+            if let Ok(mut q) = ACTIVITY_RESULTS.lock() {
+                q.push(ActivityResult {
+                    request_code,
+                    ok: result_code == RESULT_OK,
+                    uris,
+                });
+            }
+            crate::worker::wake_ui();
+            Ok(())
+        })
+        .resolve::<LogErrorAndDefault>()
+}
+
+/// Set for the launch intent and by `MainActivity.onNewIntentNative`, which also
+/// makes it the activity's `getIntent()`.
+static NEW_INTENT: AtomicBool = AtomicBool::new(true);
+
+/// Whether an intent arrived that [`incoming_intent`] hasn't been asked about.
+pub fn take_new_intent() -> bool {
+    NEW_INTENT.swap(false, Ordering::Relaxed)
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_dev_mlm_wifi_1exporter_MainActivity_onNewIntentNative<'caller>(
+    _unowned_env: EnvUnowned<'caller>,
+    _this: JObject<'caller>,
+) {
+    NEW_INTENT.store(true, Ordering::Relaxed);
+    crate::worker::wake_ui();
+}
+
 fn with_env_and_activity<F, T>(app: &AndroidApp, f: F) -> anyhow::Result<T>
 where
     F: FnOnce(&mut Env<'_>, &JObject<'_>) -> anyhow::Result<T>,
@@ -26,6 +112,405 @@ where
     })
 }
 
+fn uri_to_string(env: &mut Env<'_>, uri: &JObject<'_>) -> jni::errors::Result<String> {
+    let s = env
+        .call_method(
+            uri,
+            jni_str!("toString"),
+            jni_sig!("()Ljava/lang/String;"),
+            &[],
+        )?
+        .l()?;
+    env.cast_local::<JString>(s)?.try_to_string(env)
+}
+
+/// Every URI an intent carries: `getClipData()` items (multi-select, shares),
+/// else `getData()`.
+fn intent_uris(env: &mut Env<'_>, intent: &JObject<'_>) -> jni::errors::Result<Vec<String>> {
+    let mut out = Vec::new();
+    let clip = env
+        .call_method(
+            intent,
+            jni_str!("getClipData"),
+            jni_sig!("()Landroid/content/ClipData;"),
+            &[],
+        )?
+        .l()?;
+    if !clip.is_null() {
+        let n = env
+            .call_method(&clip, jni_str!("getItemCount"), jni_sig!("()I"), &[])?
+            .i()?;
+        for i in 0..n {
+            let item = env
+                .call_method(
+                    &clip,
+                    jni_str!("getItemAt"),
+                    jni_sig!("(I)Landroid/content/ClipData$Item;"),
+                    &[JValue::Int(i)],
+                )?
+                .l()?;
+            let uri = env
+                .call_method(
+                    &item,
+                    jni_str!("getUri"),
+                    jni_sig!("()Landroid/net/Uri;"),
+                    &[],
+                )?
+                .l()?;
+            if !uri.is_null() {
+                out.push(uri_to_string(env, &uri)?);
+            }
+        }
+    }
+    if out.is_empty() {
+        let uri = env
+            .call_method(
+                intent,
+                jni_str!("getData"),
+                jni_sig!("()Landroid/net/Uri;"),
+                &[],
+            )?
+            .l()?;
+        if !uri.is_null() {
+            out.push(uri_to_string(env, &uri)?);
+        }
+    }
+    Ok(out)
+}
+
+/// Content handed to us by "Open with" (`ACTION_VIEW`) or "Share to" (`ACTION_SEND`).
+pub enum IncomingIntent {
+    Uris(Vec<String>),
+    Text(String),
+}
+
+/// Reads the activity's current intent, if it carries an export. Reading shared
+/// content may block, so call it off the UI thread.
+pub fn incoming_intent(app: &AndroidApp) -> anyhow::Result<Option<IncomingIntent>> {
+    with_env_and_activity(app, |env, activity| {
+        let intent = env
+            .call_method(
+                activity,
+                jni_str!("getIntent"),
+                jni_sig!("()Landroid/content/Intent;"),
+                &[],
+            )?
+            .l()?;
+        if intent.is_null() {
+            return Ok(None);
+        }
+        let action = env
+            .call_method(
+                &intent,
+                jni_str!("getAction"),
+                jni_sig!("()Ljava/lang/String;"),
+                &[],
+            )?
+            .l()?;
+        if action.is_null() {
+            return Ok(None);
+        }
+        let action = env.cast_local::<JString>(action)?.try_to_string(env)?;
+        match action.as_str() {
+            "android.intent.action.VIEW" | "android.intent.action.SEND" => {}
+            _ => return Ok(None),
+        }
+
+        let uris = intent_uris(env, &intent)?;
+        if !uris.is_empty() {
+            return Ok(Some(IncomingIntent::Uris(uris)));
+        }
+        // No ClipData: a plain EXTRA_STREAM, or text shared inline.
+        let extra_stream = env
+            .get_static_field(
+                jni_str!("android/content/Intent"),
+                jni_str!("EXTRA_STREAM"),
+                jni_sig!("Ljava/lang/String;"),
+            )?
+            .l()?;
+        let stream = env
+            .call_method(
+                &intent,
+                jni_str!("getParcelableExtra"),
+                jni_sig!("(Ljava/lang/String;)Landroid/os/Parcelable;"),
+                &[JValue::Object(&extra_stream)],
+            )?
+            .l()?;
+        if !stream.is_null() {
+            return Ok(Some(IncomingIntent::Uris(vec![uri_to_string(
+                env, &stream,
+            )?])));
+        }
+        let extra_text = env
+            .get_static_field(
+                jni_str!("android/content/Intent"),
+                jni_str!("EXTRA_TEXT"),
+                jni_sig!("Ljava/lang/String;"),
+            )?
+            .l()?;
+        let text = env
+            .call_method(
+                &intent,
+                jni_str!("getStringExtra"),
+                jni_sig!("(Ljava/lang/String;)Ljava/lang/String;"),
+                &[JValue::Object(&extra_text)],
+            )?
+            .l()?;
+        if text.is_null() {
+            return Ok(None);
+        }
+        let text = env.cast_local::<JString>(text)?.try_to_string(env)?;
+        Ok(Some(IncomingIntent::Text(text)))
+    })
+}
+
+/// Whether SAF picker results can reach us; see [`RESULT_FORWARDING_ACTIVITY`].
+pub fn can_receive_activity_results(app: &AndroidApp) -> bool {
+    let name = with_env_and_activity(app, |env, activity| {
+        let class = env.get_object_class(activity)?;
+        let name = env
+            .call_method(
+                &class,
+                jni_str!("getName"),
+                jni_sig!("()Ljava/lang/String;"),
+                &[],
+            )?
+            .l()?;
+        Ok(env.cast_local::<JString>(name)?.try_to_string(env)?)
+    });
+    matches!(name.as_deref(), Ok(RESULT_FORWARDING_ACTIVITY))
+}
+
+/// Opens the system document picker; the choice arrives through
+/// [`next_activity_result`] with [`REQUEST_OPEN_DOCUMENT`].
+pub fn open_documents(app: &AndroidApp, multiple: bool) -> anyhow::Result<()> {
+    with_env_and_activity(app, |env, activity| {
+        let action = env
+            .get_static_field(
+                jni_str!("android/content/Intent"),
+                jni_str!("ACTION_OPEN_DOCUMENT"),
+                jni_sig!("Ljava/lang/String;"),
+            )?
+            .l()?;
+        let intent = env.new_object(
+            jni_str!("android/content/Intent"),
+            jni_sig!("(Ljava/lang/String;)V"),
+            &[JValue::Object(&action)],
+        )?;
+
+        let openable = env
+            .get_static_field(
+                jni_str!("android/content/Intent"),
+                jni_str!("CATEGORY_OPENABLE"),
+                jni_sig!("Ljava/lang/String;"),
+            )?
+            .l()?;
+        let _ = env.call_method(
+            &intent,
+            jni_str!("addCategory"),
+            jni_sig!("(Ljava/lang/String;)Landroid/content/Intent;"),
+            &[JValue::Object(&openable)],
+        )?;
+
+        // Exports come with all sorts of MIME types (or none), so accept anything.
+        let any = JObject::from(env.new_string("*/*")?);
+        let _ = env.call_method(
+            &intent,
+            jni_str!("setType"),
+            jni_sig!("(Ljava/lang/String;)Landroid/content/Intent;"),
+            &[JValue::Object(&any)],
+        )?;
+
+        let allow_multiple = env
+            .get_static_field(
+                jni_str!("android/content/Intent"),
+                jni_str!("EXTRA_ALLOW_MULTIPLE"),
+                jni_sig!("Ljava/lang/String;"),
+            )?
+            .l()?;
+        let _ = env.call_method(
+            &intent,
+            jni_str!("putExtra"),
+            jni_sig!("(Ljava/lang/String;Z)Landroid/content/Intent;"),
+            &[JValue::Object(&allow_multiple), JValue::Bool(multiple)],
+        )?;
+
+        env.call_method(
+            activity,
+            jni_str!("startActivityForResult"),
+            jni_sig!("(Landroid/content/Intent;I)V"),
+            &[JValue::Object(&intent), JValue::Int(REQUEST_OPEN_DOCUMENT)],
+        )?;
+        Ok(())
+    })
+}
+
+/// Opens the system "Save as" dialog with `name` prefilled; the chosen URI arrives
+/// through [`next_activity_result`] with [`REQUEST_CREATE_DOCUMENT`].
+pub fn create_document(app: &AndroidApp, name: &str, mime: &str) -> anyhow::Result<()> {
+    with_env_and_activity(app, |env, activity| {
+        let action = env
+            .get_static_field(
+                jni_str!("android/content/Intent"),
+                jni_str!("ACTION_CREATE_DOCUMENT"),
+                jni_sig!("Ljava/lang/String;"),
+            )?
+            .l()?;
+        let intent = env.new_object(
+            jni_str!("android/content/Intent"),
+            jni_sig!("(Ljava/lang/String;)V"),
+            &[JValue::Object(&action)],
+        )?;
+
+        let openable = env
+            .get_static_field(
+                jni_str!("android/content/Intent"),
+                jni_str!("CATEGORY_OPENABLE"),
+                jni_sig!("Ljava/lang/String;"),
+            )?
+            .l()?;
+        let _ = env.call_method(
+            &intent,
+            jni_str!("addCategory"),
+            jni_sig!("(Ljava/lang/String;)Landroid/content/Intent;"),
+            &[JValue::Object(&openable)],
+        )?;
+
+        let jmime = JObject::from(env.new_string(mime)?);
+        let _ = env.call_method(
+            &intent,
+            jni_str!("setType"),
+            jni_sig!("(Ljava/lang/String;)Landroid/content/Intent;"),
+            &[JValue::Object(&jmime)],
+        )?;
+
+        let extra_title = env
+            .get_static_field(
+                jni_str!("android/content/Intent"),
+                jni_str!("EXTRA_TITLE"),
+                jni_sig!("Ljava/lang/String;"),
+            )?
+            .l()?;
+        let jname = JObject::from(env.new_string(name)?);
+        let _ = env.call_method(
+            &intent,
+            jni_str!("putExtra"),
+            jni_sig!("(Ljava/lang/String;Ljava/lang/String;)Landroid/content/Intent;"),
+            &[JValue::Object(&extra_title), JValue::Object(&jname)],
+        )?;
+
+        env.call_method(
+            activity,
+            jni_str!("startActivityForResult"),
+            jni_sig!("(Landroid/content/Intent;I)V"),
+            &[
+                JValue::Object(&intent),
+                JValue::Int(REQUEST_CREATE_DOCUMENT),
+            ],
+        )?;
+        Ok(())
+    })
+}
+
+fn content_resolver_and_uri<'local>(
+    env: &mut Env<'local>,
+    activity: &JObject<'_>,
+    uri: &str,
+) -> anyhow::Result<(JObject<'local>, JObject<'local>)> {
+    let resolver = env
+        .call_method(
+            activity,
+            jni_str!("getContentResolver"),
+            jni_sig!("()Landroid/content/ContentResolver;"),
+            &[],
+        )?
+        .l()?;
+    let juri_str = JObject::from(env.new_string(uri)?);
+    let juri = env
+        .call_static_method(
+            jni_str!("android/net/Uri"),
+            jni_str!("parse"),
+            jni_sig!("(Ljava/lang/String;)Landroid/net/Uri;"),
+            &[JValue::Object(&juri_str)],
+        )?
+        .l()?;
+    Ok((resolver, juri))
+}
+
+/// Replaces the contents of a `content://` URI we hold a write grant for.
+pub fn write_content_uri(app: &AndroidApp, uri: &str, contents: &[u8]) -> anyhow::Result<()> {
+    with_env_and_activity(app, |env, activity| {
+        let (resolver, juri) = content_resolver_and_uri(env, activity, uri)?;
+        // "wt" truncates; plain "w" leaves stale bytes behind on some providers.
+        let mode = JObject::from(env.new_string("wt")?);
+        let os = env
+            .call_method(
+                &resolver,
+                jni_str!("openOutputStream"),
+                jni_sig!("(Landroid/net/Uri;Ljava/lang/String;)Ljava/io/OutputStream;"),
+                &[JValue::Object(&juri), JValue::Object(&mode)],
+            )?
+            .l()?;
+        if os.is_null() {
+            return Err(anyhow::anyhow!("Provider returned no stream"));
+        }
+        let bytes = env.byte_array_from_slice(contents)?;
+        let written = env.call_method(
+            &os,
+            jni_str!("write"),
+            jni_sig!("([B)V"),
+            &[JValue::Object(&bytes)],
+        );
+        let closed = env.call_method(&os, jni_str!("close"), jni_sig!("()V"), &[]);
+        written?;
+        closed?;
+        Ok(())
+    })
+}
+
+/// Reads a `content://` URI we hold a grant for through `ContentResolver`. Cloud
+/// providers may download the file first, so call it off the UI thread.
+pub fn read_content_uri(app: &AndroidApp, uri: &str) -> anyhow::Result<Vec<u8>> {
+    with_env_and_activity(app, |env, activity| {
+        let (resolver, juri) = content_resolver_and_uri(env, activity, uri)?;
+        let is = env
+            .call_method(
+                &resolver,
+                jni_str!("openInputStream"),
+                jni_sig!("(Landroid/net/Uri;)Ljava/io/InputStream;"),
+                &[JValue::Object(&juri)],
+            )?
+            .l()?;
+        if is.is_null() {
+            return Err(anyhow::anyhow!("Provider returned no stream"));
+        }
+
+        let buf: JByteArray = env.new_byte_array(64 * 1024)?;
+        let mut out = Vec::new();
+        let result = loop {
+            let n = match env.call_method(
+                &is,
+                jni_str!("read"),
+                jni_sig!("([B)I"),
+                &[JValue::Object(&buf)],
+            ) {
+                Ok(v) => v.i()?,
+                Err(e) => break Err(e.into()),
+            };
+            if n < 0 {
+                break Ok(());
+            }
+            let chunk = env.convert_byte_array(&buf)?;
+            out.extend_from_slice(&chunk[..n as usize]);
+            if out.len() > MAX_DOCUMENT_BYTES {
+                break Err(anyhow::anyhow!("Document is larger than 16 MiB"));
+            }
+        };
+        let _ = env.call_method(&is, jni_str!("close"), jni_sig!("()V"), &[]);
+        result.map(|_| out)
+    })
+}
+
 fn sdk_int(env: &mut Env<'_>) -> anyhow::Result<i32> {
     let v = env
         .get_static_field(
@@ -37,10 +522,12 @@ fn sdk_int(env: &mut Env<'_>) -> anyhow::Result<i32> {
     Ok(v)
 }
 
-pub fn write_json_via_mediastore(
+/// Writes `contents` into Downloads.
+pub fn write_file_via_mediastore(
     app: &AndroidApp,
     name: &str,
-    json: &str,
+    mime: &str,
+    contents: &[u8],
 ) -> anyhow::Result<Option<String>> {
     with_env_and_activity(app, |env, activity| {
         env.ensure_local_capacity(64)?;
@@ -50,7 +537,7 @@ pub fn write_json_via_mediastore(
         let jname = env.new_string(name)?;
         let jname_obj = JObject::from(jname);
 
-        let jmime = env.new_string("application/json")?;
+        let jmime = env.new_string(mime)?;
         let jmime_obj = JObject::from(jmime);
 
         if api >= 29 {
@@ -130,7 +617,7 @@ pub fn write_json_via_mediastore(
                 ],
             )?;
 
-            // cv.put(MIME_TYPE, "application/json")
+            // cv.put(MIME_TYPE, mime)
             env.call_method(
                 &cv,
                 jni_str!("put"),
@@ -196,7 +683,7 @@ pub fn write_json_via_mediastore(
             if os.is_null() {
                 return Ok(None);
             }
-            let bytes = env.byte_array_from_slice(json.as_bytes())?;
+            let bytes = env.byte_array_from_slice(contents)?;
             let bytes_obj = JObject::from(bytes);
             env.call_method(
                 &os,
@@ -284,7 +771,7 @@ pub fn write_json_via_mediastore(
                 jni_sig!("(Ljava/io/File;)V"),
                 &[JValue::Object(&file)],
             )?;
-            let bytes = env.byte_array_from_slice(json.as_bytes())?;
+            let bytes = env.byte_array_from_slice(contents)?;
             let bytes_obj = JObject::from(bytes);
             env.call_method(
                 &fos,
@@ -311,6 +798,304 @@ pub fn write_json_via_mediastore(
     })
 }
 
+/// FileProvider authority declared in `android/app/src/main/AndroidManifest.xml`.
+const SHARE_AUTHORITY: &str = "dev.mlm.wifi_exporter.files";
+
+/// Cache subfolder `res/xml/share_paths.xml` exposes; emptied by
+/// [`cleanup_shared_files`].
+const SHARE_DIR: &str = "shares";
+
+/// Downloads subfolder older versions shared from.
+const LEGACY_SHARE_SUBDIR: &str = "WiFi Exporter shares";
+
+/// `File(getCacheDir(), SHARE_DIR)`, created if missing.
+fn share_dir<'local>(
+    env: &mut Env<'local>,
+    activity: &JObject<'_>,
+) -> jni::errors::Result<JObject<'local>> {
+    let cache = env
+        .call_method(
+            activity,
+            jni_str!("getCacheDir"),
+            jni_sig!("()Ljava/io/File;"),
+            &[],
+        )?
+        .l()?;
+    let name = JObject::from(env.new_string(SHARE_DIR)?);
+    let dir = env.new_object(
+        jni_str!("java/io/File"),
+        jni_sig!("(Ljava/io/File;Ljava/lang/String;)V"),
+        &[JValue::Object(&cache), JValue::Object(&name)],
+    )?;
+    let _ = env.call_method(&dir, jni_str!("mkdirs"), jni_sig!("()Z"), &[])?;
+    Ok(dir)
+}
+
+/// Deletes the files earlier shares left in the cache, and any an older version
+/// left in Downloads (only our own MediaStore entries, so no prompt).
+pub fn cleanup_shared_files(app: &AndroidApp) -> anyhow::Result<i32> {
+    with_env_and_activity(app, |env, activity| {
+        let dir = share_dir(env, activity)?;
+        let files = env
+            .call_method(
+                &dir,
+                jni_str!("listFiles"),
+                jni_sig!("()[Ljava/io/File;"),
+                &[],
+            )?
+            .l()?;
+        let mut n = 0;
+        if !files.is_null() {
+            let files = env.cast_local::<JObjectArray>(files)?;
+            for i in 0..files.len(env)? {
+                let file = files.get_element(env, i)?;
+                if env
+                    .call_method(&file, jni_str!("delete"), jni_sig!("()Z"), &[])?
+                    .z()?
+                {
+                    n += 1;
+                }
+            }
+        }
+
+        if sdk_int(env)? < 29 {
+            return Ok(n);
+        }
+        let resolver = env
+            .call_method(
+                activity,
+                jni_str!("getContentResolver"),
+                jni_sig!("()Landroid/content/ContentResolver;"),
+                &[],
+            )?
+            .l()?;
+        let downloads_uri = env
+            .get_static_field(
+                jni_str!("android/provider/MediaStore$Downloads"),
+                jni_str!("EXTERNAL_CONTENT_URI"),
+                jni_sig!("Landroid/net/Uri;"),
+            )?
+            .l()?;
+        let selection = JObject::from(env.new_string("relative_path=?")?);
+        let rel = JObject::from(env.new_string(format!("Download/{}/", LEGACY_SHARE_SUBDIR))?);
+        let args = env.new_object_array(1, jni_str!("java/lang/String"), &rel)?;
+        let legacy = env
+            .call_method(
+                &resolver,
+                jni_str!("delete"),
+                jni_sig!("(Landroid/net/Uri;Ljava/lang/String;[Ljava/lang/String;)I"),
+                &[
+                    JValue::Object(&downloads_uri),
+                    JValue::Object(&selection),
+                    JValue::Object(&args),
+                ],
+            )?
+            .i()?;
+        Ok(n + legacy)
+    })
+}
+
+/// Shares export files as attachments (`EXTRA_STREAM`) rather than text, so
+/// messengers can't truncate them and nothing lands in clipboard history. The
+/// files stay in the app cache behind the FileProvider and are deleted once
+/// the share sheet returns ([`REQUEST_SHARE`]). Returns `Ok(false)` in
+/// `cargo apk` builds, which have no FileProvider.
+pub fn share_files(
+    app: &AndroidApp,
+    title: &str,
+    mime: &str,
+    files: &[(&str, &[u8])],
+) -> anyhow::Result<bool> {
+    if files.is_empty() {
+        return Ok(false);
+    }
+    // Anything still there from the last share has been read by now.
+    if let Err(e) = cleanup_shared_files(app) {
+        log::warn!("Share cleanup failed: {e:?}");
+    }
+
+    with_env_and_activity(app, |env, activity| {
+        // App classes aren't visible to FindClass from native threads.
+        let provider = match LoaderContext::FromObject(activity).load_class(
+            env,
+            jni_str!("androidx.core.content.FileProvider"),
+            true,
+        ) {
+            Ok(class) => class,
+            Err(e) => {
+                log::info!("No FileProvider, can't share files: {e}");
+                return Ok(false);
+            }
+        };
+        let dir = share_dir(env, activity)?;
+        let authority = JObject::from(env.new_string(SHARE_AUTHORITY)?);
+        let mut uris = Vec::with_capacity(files.len());
+        for (name, contents) in files {
+            let jname = JObject::from(env.new_string(name)?);
+            let file = env.new_object(
+                jni_str!("java/io/File"),
+                jni_sig!("(Ljava/io/File;Ljava/lang/String;)V"),
+                &[JValue::Object(&dir), JValue::Object(&jname)],
+            )?;
+            let fos = env.new_object(
+                jni_str!("java/io/FileOutputStream"),
+                jni_sig!("(Ljava/io/File;)V"),
+                &[JValue::Object(&file)],
+            )?;
+            let bytes = JObject::from(env.byte_array_from_slice(contents)?);
+            let written = env.call_method(
+                &fos,
+                jni_str!("write"),
+                jni_sig!("([B)V"),
+                &[JValue::Object(&bytes)],
+            );
+            let closed = env.call_method(&fos, jni_str!("close"), jni_sig!("()V"), &[]);
+            written?;
+            closed?;
+            let uri = env
+                .call_static_method(
+                    &provider,
+                    jni_str!("getUriForFile"),
+                    jni_sig!(
+                        "(Landroid/content/Context;Ljava/lang/String;Ljava/io/File;)Landroid/net/Uri;"
+                    ),
+                    &[
+                        JValue::Object(activity),
+                        JValue::Object(&authority),
+                        JValue::Object(&file),
+                    ],
+                )?
+                .l()?;
+            uris.push(uri);
+        }
+
+        let action_name = if uris.len() == 1 {
+            jni_str!("ACTION_SEND")
+        } else {
+            jni_str!("ACTION_SEND_MULTIPLE")
+        };
+        let action = env
+            .get_static_field(
+                jni_str!("android/content/Intent"),
+                action_name,
+                jni_sig!("Ljava/lang/String;"),
+            )?
+            .l()?;
+        let intent = env.new_object(
+            jni_str!("android/content/Intent"),
+            jni_sig!("(Ljava/lang/String;)V"),
+            &[JValue::Object(&action)],
+        )?;
+
+        let jmime = JObject::from(env.new_string(mime)?);
+        let _ = env.call_method(
+            &intent,
+            jni_str!("setType"),
+            jni_sig!("(Ljava/lang/String;)Landroid/content/Intent;"),
+            &[JValue::Object(&jmime)],
+        )?;
+
+        let extra_stream = env
+            .get_static_field(
+                jni_str!("android/content/Intent"),
+                jni_str!("EXTRA_STREAM"),
+                jni_sig!("Ljava/lang/String;"),
+            )?
+            .l()?;
+        let list = env.new_object(jni_str!("java/util/ArrayList"), jni_sig!("()V"), &[])?;
+        let mut clip = JObject::null();
+        for juri in &uris {
+            let _ = env.call_method(
+                &list,
+                jni_str!("add"),
+                jni_sig!("(Ljava/lang/Object;)Z"),
+                &[JValue::Object(juri)],
+            )?;
+            // The chooser only forwards grants for URIs in ClipData.
+            if clip.is_null() {
+                let label = JObject::from(env.new_string(title)?);
+                clip = env
+                    .call_static_method(
+                        jni_str!("android/content/ClipData"),
+                        jni_str!("newRawUri"),
+                        jni_sig!(
+                            "(Ljava/lang/CharSequence;Landroid/net/Uri;)Landroid/content/ClipData;"
+                        ),
+                        &[JValue::Object(&label), JValue::Object(juri)],
+                    )?
+                    .l()?;
+            } else {
+                let item = env.new_object(
+                    jni_str!("android/content/ClipData$Item"),
+                    jni_sig!("(Landroid/net/Uri;)V"),
+                    &[JValue::Object(juri)],
+                )?;
+                env.call_method(
+                    &clip,
+                    jni_str!("addItem"),
+                    jni_sig!("(Landroid/content/ClipData$Item;)V"),
+                    &[JValue::Object(&item)],
+                )?;
+            }
+            if uris.len() == 1 {
+                let _ = env.call_method(
+                    &intent,
+                    jni_str!("putExtra"),
+                    jni_sig!("(Ljava/lang/String;Landroid/os/Parcelable;)Landroid/content/Intent;"),
+                    &[JValue::Object(&extra_stream), JValue::Object(juri)],
+                )?;
+            }
+        }
+        if uris.len() > 1 {
+            let _ = env.call_method(
+                &intent,
+                jni_str!("putParcelableArrayListExtra"),
+                jni_sig!("(Ljava/lang/String;Ljava/util/ArrayList;)Landroid/content/Intent;"),
+                &[JValue::Object(&extra_stream), JValue::Object(&list)],
+            )?;
+        }
+        env.call_method(
+            &intent,
+            jni_str!("setClipData"),
+            jni_sig!("(Landroid/content/ClipData;)V"),
+            &[JValue::Object(&clip)],
+        )?;
+
+        let grant = env
+            .get_static_field(
+                jni_str!("android/content/Intent"),
+                jni_str!("FLAG_GRANT_READ_URI_PERMISSION"),
+                jni_sig!("I"),
+            )?
+            .i()?;
+        let _ = env.call_method(
+            &intent,
+            jni_str!("addFlags"),
+            jni_sig!("(I)Landroid/content/Intent;"),
+            &[JValue::Int(grant)],
+        )?;
+
+        let jtitle = JObject::from(env.new_string(title)?);
+        let chooser = env
+            .call_static_method(
+                jni_str!("android/content/Intent"),
+                jni_str!("createChooser"),
+                jni_sig!(
+                    "(Landroid/content/Intent;Ljava/lang/CharSequence;)Landroid/content/Intent;"
+                ),
+                &[JValue::Object(&intent), JValue::Object(&jtitle)],
+            )?
+            .l()?;
+        env.call_method(
+            activity,
+            jni_str!("startActivityForResult"),
+            jni_sig!("(Landroid/content/Intent;I)V"),
+            &[JValue::Object(&chooser), JValue::Int(REQUEST_SHARE)],
+        )?;
+        Ok(true)
+    })
+}
+
 pub fn share_text(app: &AndroidApp, title: &str, text: &str) -> anyhow::Result<()> {
     with_env_and_activity(app, |env, activity| {
         // Intent(ACTION_SEND)
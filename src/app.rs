@@ -0,0 +1,2218 @@
+//! The repose UI: signals, screens and the glue between them, the worker and
+//! the JNI helpers.
+
+use crate::android_jni::{
+    self, IncomingIntent, share_files, share_text, write_file_via_mediastore,
+};
+use crate::diagnostics::{self, Check, CheckState};
+use crate::error::{ImportError, SuError};
+use crate::parsers::{
+    AndroidUser, CSV_DELIMITERS, ConfigSearch, CsvMapping, CsvOptions, ExportFile, ExportFormat,
+    ExportOptions, ImportOptions, ImportSummary, ParsedImport, RejectedEntry, SuTimeouts,
+    SystemNetworks, UciOptions, WifiCred, build_export, build_json, get_api_level,
+    parse_import_bytes, su_add_network, su_import_all, su_read, try_read_with_su,
+};
+use crate::worker::{Event, Outcome, Task};
+use crate::{crypto, parsers, worker};
+use log::{LevelFilter, warn};
+use repose_core::prelude::*;
+use repose_platform::RenderContext;
+use repose_platform::android::run_android_app;
+use repose_ui::scroll::{ScrollArea, remember_scroll_state};
+use repose_ui::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use winit::platform::android::activity::AndroidApp;
+use zeroize::Zeroizing;
+
+static ANDROID_APP: OnceLock<AndroidApp> = OnceLock::new();
+
+/// Files still waiting for a "Save as" destination; the last one is the one
+/// whose picker is open.
+static PENDING_SAVES: Mutex<Vec<ExportFile>> = Mutex::new(Vec::new());
+
+const MIN_IMPORT_API: i32 = 30;
+
+const IMPORT_DIRS: [&str; 2] = ["/sdcard/Download", "/storage/emulated/0/Download"];
+const IMPORT_EXTENSIONS: [&str; 10] = [
+    "json",
+    crypto::EXTENSION,
+    crypto::AGE_EXTENSION,
+    "csv",
+    "xml",
+    "mobileconfig",
+    "uci",
+    "conf",
+    "nmconnection",
+    "txt",
+];
+
+/// Rows of the skipped-entries panel; the rest only go to the log.
+const MAX_REJECTED_SHOWN: usize = 20;
+
+/// Kept in the app's private storage, never in shared folders.
+const AGE_RECIPIENTS_FILE: &str = "age_recipients.txt";
+const AGE_IDENTITY_FILE: &str = "age_identity.txt";
+/// Extra "Load System" search paths, one per line; private storage.
+const CONFIG_PATHS_FILE: &str = "config_paths.txt";
+
+fn app(_s: &mut Scheduler, _rc: &RenderContext) -> View {
+    let creds = remember(|| signal(Vec::<WifiCred>::new()));
+    let status = remember(|| signal(String::from("Ready")));
+    let json_buf = remember(|| signal(String::new()));
+    let source_path = remember(|| signal(None::<String>));
+    let export_format = remember(|| signal(ExportFormat::Json));
+    let csv_delimiter = remember(|| signal(','));
+    let age_recipients = remember(|| signal(load_internal_text(AGE_RECIPIENTS_FILE)));
+    let uci_device = remember(|| signal(UciOptions::default().device));
+    let uci_network = remember(|| signal(UciOptions::default().network));
+    let uci_disabled = remember(|| signal(UciOptions::default().disabled));
+    let extra_config_paths = remember(|| signal(load_internal_text(CONFIG_PATHS_FILE)));
+    let discover_configs = remember(|| signal(false));
+    let users = remember(|| signal(Vec::<AndroidUser>::new()));
+    let convert_suggestions = remember(|| signal(false));
+    let sort_order = remember(|| signal(SortOrder::Name));
+    let age_filter = remember(|| signal(AgeFilter::All));
+    let lenient_import = remember(|| signal(false));
+    let rejected = remember(|| signal(Vec::<RejectedEntry>::new()));
+    let diagnostics = remember(|| signal(None::<Vec<Check>>));
+    let su_timeout = remember(|| signal(parsers::su_timeouts().command.as_secs()));
+    let su_overall = remember(|| signal(parsers::su_timeouts().overall.as_secs() / 60));
+    let add_rows = remember(|| signal(HashMap::<usize, AddState>::new()));
+    let add_progress = remember(|| signal(None::<(usize, usize)>));
+    let prompt = remember(|| signal(None::<PassphrasePrompt>));
+    let passphrase = remember(|| signal(String::new()));
+    let passphrase_confirm = remember(|| signal(String::new()));
+    let api_level = get_api_level();
+    let can_import = api_level >= MIN_IMPORT_API;
+
+    let sink = ImportSink {
+        creds: (*creds).clone(),
+        status: (*status).clone(),
+        json_buf: (*json_buf).clone(),
+        source_path: (*source_path).clone(),
+        rejected: (*rejected).clone(),
+        users: (*users).clone(),
+        add_rows: (*add_rows).clone(),
+    };
+    let tracker = AddTracker {
+        rows: (*add_rows).clone(),
+        progress: (*add_progress).clone(),
+    };
+    // The launch intent, or one `onNewIntent` delivered; read once the worker is free.
+    if worker::running().is_none() && android_jni::take_new_intent() {
+        read_incoming_intent();
+    }
+    drain_activity_results(&sink);
+    drain_worker_events(&sink, &prompt, &tracker, &diagnostics, lenient_import.get());
+    let can_pick = *remember(|| {
+        ANDROID_APP
+            .get()
+            .is_some_and(android_jni::can_receive_activity_results)
+    });
+    let load_system_action = load_system(
+        (*status).clone(),
+        (*discover_configs).clone(),
+        (*extra_config_paths).clone(),
+    );
+    let load_file_action = load_file((*status).clone());
+    let import_to_system_action = import_to_system(
+        (*creds).clone(),
+        (*status).clone(),
+        tracker.clone(),
+        (*convert_suggestions).clone(),
+    );
+    let export_settings = ExportSettings {
+        format: (*export_format).clone(),
+        csv_delimiter: (*csv_delimiter).clone(),
+        age_recipients: (*age_recipients).clone(),
+        uci_device: (*uci_device).clone(),
+        uci_network: (*uci_network).clone(),
+        uci_disabled: (*uci_disabled).clone(),
+    };
+    let save_action = save_export(
+        (*creds).clone(),
+        (*status).clone(),
+        export_settings.clone(),
+        (*source_path).clone(),
+        (*prompt).clone(),
+    );
+    let save_as_action = {
+        let save = save_action.clone();
+        move || save(SaveTarget::Picked)
+    };
+    let save_action = move || save_action(SaveTarget::Downloads);
+    let share_action = share_json(
+        (*creds).clone(),
+        (*status).clone(),
+        export_settings.clone(),
+        (*source_path).clone(),
+        (*prompt).clone(),
+    );
+
+    let import_btn_color = if can_import {
+        Color::from_hex("#c53f16ff")
+    } else {
+        Color::from_hex("#555555")
+    };
+
+    let api_text = if can_import {
+        format!("API {} | Import: ✓", api_level)
+    } else {
+        format!("API {} | Import: ✗ (needs 11+)", api_level)
+    };
+
+    let api_color = if can_import {
+        Color::from_hex("#888888")
+    } else {
+        Color::from_hex("#FF8888")
+    };
+
+    let status_for_list = (*status).clone();
+    let now = ts_secs();
+    let shown = shown_networks(creds.get(), sort_order.get(), age_filter.get(), now);
+
+    Surface(
+        Modifier::new()
+            .fill_max_size()
+            .background(Color::from_hex("#121212")),
+        Column(Modifier::new().fill_max_size().padding(24.0)).with_children(vec![
+            Space(Modifier::new().height(16.0)),
+            Text("WiFi Passwords").size(22.0).color(Color::WHITE),
+            Text(api_text).size(12.0).color(api_color),
+            Space(Modifier::new().height(16.0)),
+            Row(Modifier::new().fill_max_width()).with_children(vec![
+                styled_button(
+                    "Load System",
+                    Color::from_hex("#2186F3"),
+                    load_system_action,
+                ),
+                Space(Modifier::new().width(8.0)),
+                styled_button("Load File", Color::from_hex("#2186F3"), load_file_action),
+            ]),
+            pick_files_row(can_pick, (*status).clone()),
+            busy_row(),
+            lenient_toggle((*lenient_import).clone()),
+            su_timeout_toggle((*su_timeout).clone(), (*su_overall).clone()),
+            config_search_options((*discover_configs).clone(), (*extra_config_paths).clone()),
+            diagnostics_view(
+                (*diagnostics).clone(),
+                (*status).clone(),
+                (*discover_configs).clone(),
+                (*extra_config_paths).clone(),
+            ),
+            Space(Modifier::new().height(30.0)),
+            format_selector((*export_format).clone()),
+            csv_options((*export_format).clone(), (*csv_delimiter).clone()),
+            age_options((*export_format).clone(), (*age_recipients).clone()),
+            uci_options(&export_settings),
+            Space(Modifier::new().height(8.0)),
+            Row(Modifier::new().fill_max_width()).with_children(vec![
+                styled_button(
+                    &format!("Save {}", export_format.get().label()),
+                    Color::from_hex("#4CAF50"),
+                    save_action,
+                ),
+                Space(Modifier::new().width(8.0)),
+                styled_button("Share", Color::from_hex("#4CAF50"), share_action),
+            ]),
+            save_as_row(can_pick, save_as_action),
+            Space(Modifier::new().height(30.0)),
+            current_user_note(&users.get()),
+            suggestions_toggle(&creds.get(), (*convert_suggestions).clone()),
+            Button(
+                Text("Import All to System").size(14.0).color(Color::WHITE),
+                import_to_system_action,
+            )
+            .modifier(
+                Modifier::new()
+                    .fill_max_width()
+                    .padding(12.0)
+                    .background(import_btn_color)
+                    .clip_rounded(8.0),
+            ),
+            passphrase_dialog(
+                (*prompt).clone(),
+                (*passphrase).clone(),
+                (*passphrase_confirm).clone(),
+                sink.clone(),
+            ),
+            Space(Modifier::new().height(12.0)),
+            // Status
+            Text(status.get())
+                .size(13.0)
+                .color(Color::from_hex("#69F0AE")),
+            rejected_list(rejected.get()),
+            Space(Modifier::new().height(12.0)),
+            progress_bar(add_progress.get()),
+            list_controls(
+                &sink,
+                (*sort_order).clone(),
+                (*age_filter).clone(),
+                shown.len(),
+            ),
+            network_list(
+                shown,
+                now,
+                &add_rows.get(),
+                status_for_list,
+                can_import,
+                &users.get(),
+            ),
+        ]),
+    )
+}
+
+fn pick_files_row(can_pick: bool, status: Signal<String>) -> View {
+    if !can_pick {
+        return Space(Modifier::new().height(0.0));
+    }
+    Row(Modifier::new().fill_max_width().padding(4.0)).with_children(vec![styled_button(
+        "Pick Files…",
+        Color::from_hex("#1565C0"),
+        pick_files(status),
+    )])
+}
+
+fn save_as_row<F: Fn() + Clone + 'static>(can_pick: bool, action: F) -> View {
+    if !can_pick {
+        return Space(Modifier::new().height(0.0));
+    }
+    Row(Modifier::new().fill_max_width().padding(4.0)).with_children(vec![styled_button(
+        "Save As…",
+        Color::from_hex("#2E7D32"),
+        action,
+    )])
+}
+
+fn styled_button<F: Fn() + Clone + 'static>(label: &str, bg: Color, action: F) -> View {
+    Button(Text(label).size(13.0).color(Color::WHITE), action).modifier(
+        Modifier::new()
+            .weight(1.0)
+            .padding(10.0)
+            .background(bg)
+            .clip_rounded(6.0),
+    )
+}
+
+fn format_selector(export_format: Signal<ExportFormat>) -> View {
+    let current = export_format.get();
+    let children = ExportFormat::ALL
+        .into_iter()
+        .map(|fmt| {
+            let sig = export_format.clone();
+            let bg = if fmt == current {
+                Color::from_hex("#2E7D32")
+            } else {
+                Color::from_hex("#2A2A2A")
+            };
+            Box(Modifier::new().padding(3.0)).with_children(vec![
+                Button(
+                    Text(fmt.label()).size(12.0).color(Color::WHITE),
+                    move || sig.set(fmt),
+                )
+                .modifier(
+                    Modifier::new()
+                        .padding(6.0)
+                        .background(bg)
+                        .clip_rounded(6.0),
+                ),
+            ])
+        })
+        .collect();
+    FlowRow(Modifier::new().fill_max_width()).with_children(children)
+}
+
+fn csv_options(export_format: Signal<ExportFormat>, csv_delimiter: Signal<char>) -> View {
+    if export_format.get() != ExportFormat::Csv {
+        return Space(Modifier::new().height(0.0));
+    }
+    let current = csv_delimiter.get();
+    let label = match current {
+        '\t' => "Tab".to_string(),
+        c => format!("'{}'", c),
+    };
+    // Every delimiter the importer sniffs, in order.
+    let next = CSV_DELIMITERS
+        .iter()
+        .position(|&d| d == current)
+        .map_or(',', |i| CSV_DELIMITERS[(i + 1) % CSV_DELIMITERS.len()]);
+    Column(Modifier::new().fill_max_width().padding(4.0)).with_children(vec![Button(
+        Text(format!("CSV delimiter: {}", label))
+            .size(12.0)
+            .color(Color::from_hex("#AAAAAA")),
+        move || csv_delimiter.set(next),
+    )])
+}
+
+fn uci_options(settings: &ExportSettings) -> View {
+    if settings.format.get() != ExportFormat::OpenWrt {
+        return Space(Modifier::new().height(0.0));
+    }
+    let (device, network, disabled) = (
+        settings.uci_device.clone(),
+        settings.uci_network.clone(),
+        settings.uci_disabled.clone(),
+    );
+    let on = disabled.get();
+    Column(Modifier::new().fill_max_width().padding(4.0)).with_children(vec![
+        TextFieldEx::new(
+            "Radio (wifi-device, empty to leave out)",
+            device.get(),
+            Modifier::new().fill_max_width(),
+        )
+        .on_change(move |s| device.set(s))
+        .build(),
+        TextFieldEx::new(
+            "Network interface (empty to leave out)",
+            network.get(),
+            Modifier::new().fill_max_width(),
+        )
+        .on_change(move |s| network.set(s))
+        .build(),
+        Button(
+            Text(if on {
+                "Start disabled: on"
+            } else {
+                "Start disabled: off"
+            })
+            .size(12.0)
+            .color(Color::from_hex("#AAAAAA")),
+            move || disabled.set(!on),
+        ),
+    ])
+}
+
+fn age_options(export_format: Signal<ExportFormat>, age_recipients: Signal<String>) -> View {
+    if export_format.get() != ExportFormat::Age {
+        return Space(Modifier::new().height(0.0));
+    }
+    let count = crypto::split_recipients(&age_recipients.get()).len();
+    let sig = age_recipients.clone();
+    Column(Modifier::new().fill_max_width().padding(4.0)).with_children(vec![
+        TextFieldEx::new(
+            "age1… recipients (comma separated)",
+            age_recipients.get(),
+            Modifier::new().fill_max_width(),
+        )
+        .on_change(move |s| sig.set(s))
+        .on_submit(|s| store_internal_text(AGE_RECIPIENTS_FILE, &s))
+        .build(),
+        Text(format!("{} recipient(s)", count))
+            .size(12.0)
+            .color(Color::from_hex("#AAAAAA")),
+    ])
+}
+
+/// Signals behind the per-format export settings.
+#[derive(Clone)]
+struct ExportSettings {
+    format: Signal<ExportFormat>,
+    csv_delimiter: Signal<char>,
+    age_recipients: Signal<String>,
+    uci_device: Signal<String>,
+    uci_network: Signal<String>,
+    uci_disabled: Signal<bool>,
+}
+
+impl ExportSettings {
+    fn options(&self, list: &[WifiCred], source_path: Option<String>) -> ExportOptions {
+        ExportOptions {
+            csv: CsvOptions {
+                delimiter: self.csv_delimiter.get(),
+                notes: list.iter().any(|c| c.notes.is_some()),
+                ..Default::default()
+            },
+            uci: UciOptions {
+                device: self.uci_device.get(),
+                network: self.uci_network.get(),
+                disabled: self.uci_disabled.get(),
+            },
+            age_recipients: self.age_recipients_for(self.format.get()),
+            source_path,
+            ..Default::default()
+        }
+    }
+
+    /// Recipients for an export, saving the list when it's actually used.
+    fn age_recipients_for(&self, format: ExportFormat) -> Vec<String> {
+        if format != ExportFormat::Age {
+            return Vec::new();
+        }
+        let text = self.age_recipients.get();
+        store_internal_text(AGE_RECIPIENTS_FILE, &text);
+        crypto::split_recipients(&text)
+    }
+}
+
+fn internal_file(name: &str) -> Option<PathBuf> {
+    ANDROID_APP
+        .get()
+        .and_then(|app| app.internal_data_path())
+        .map(|dir| dir.join(name))
+}
+
+fn load_internal_text(name: &str) -> String {
+    internal_file(name)
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .unwrap_or_default()
+}
+
+fn store_internal_text(name: &str, text: &str) {
+    if let Some(path) = internal_file(name)
+        && let Err(e) = std::fs::write(&path, text)
+    {
+        warn!("Saving {name} failed: {e:?}");
+    }
+}
+
+/// The identity pasted into the age prompt; only ever in private storage.
+fn load_age_identity() -> Option<String> {
+    Some(load_internal_text(AGE_IDENTITY_FILE)).filter(|t| !t.trim().is_empty())
+}
+
+/// Where "Save" puts the export.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum SaveTarget {
+    Downloads,
+    /// Folder and file name chosen in the system "Save as" dialog.
+    Picked,
+}
+
+/// What the dialog above the status line asks for and does once confirmed.
+#[derive(Clone)]
+enum PassphrasePrompt {
+    Save(SaveTarget),
+    Share,
+    /// Armored encrypted export waiting to be decrypted and merged.
+    Load {
+        bytes: Vec<u8>,
+        lenient: bool,
+    },
+    /// age file and no stored identity: the dialog takes the identity instead.
+    AgeIdentity {
+        bytes: Vec<u8>,
+        lenient: bool,
+    },
+    /// CSV file: the guessed columns, for the user to check before importing.
+    CsvColumns {
+        bytes: Vec<u8>,
+        mapping: CsvMapping,
+    },
+}
+
+fn passphrase_dialog(
+    prompt: Signal<Option<PassphrasePrompt>>,
+    passphrase: Signal<String>,
+    passphrase_confirm: Signal<String>,
+    sink: ImportSink,
+) -> View {
+    let Some(current) = prompt.get() else {
+        return Space(Modifier::new().height(0.0));
+    };
+    if let PassphrasePrompt::CsvColumns { bytes, mapping } = current {
+        return csv_columns_dialog(prompt, bytes, mapping, sink);
+    }
+    let encrypting = matches!(current, PassphrasePrompt::Save(_) | PassphrasePrompt::Share);
+    let (title, hint) = match current {
+        PassphrasePrompt::Save(_) => ("Passphrase to encrypt the saved file", "Passphrase"),
+        PassphrasePrompt::Share => ("Passphrase to encrypt the shared export", "Passphrase"),
+        PassphrasePrompt::Load { .. } => ("Passphrase for the encrypted import", "Passphrase"),
+        PassphrasePrompt::AgeIdentity { .. } => (
+            "age identity for this file (kept in app storage)",
+            "AGE-SECRET-KEY-1…",
+        ),
+        PassphrasePrompt::CsvColumns { .. } => unreachable!("handled above"),
+    };
+    let status = sink.status.clone();
+
+    let pass_sig = passphrase.clone();
+    let confirm_sig = passphrase_confirm.clone();
+    let clear = {
+        let (prompt, passphrase, passphrase_confirm) = (
+            prompt.clone(),
+            passphrase.clone(),
+            passphrase_confirm.clone(),
+        );
+        move || {
+            passphrase.set(String::new());
+            passphrase_confirm.set(String::new());
+            prompt.set(None);
+        }
+    };
+    let cancel = {
+        let (clear, status) = (clear.clone(), status.clone());
+        move || {
+            clear();
+            status.set("Cancelled".into());
+        }
+    };
+    let confirm = move || {
+        let pass = Zeroizing::new(passphrase.get());
+        if pass.is_empty() {
+            status.set("Passphrase is empty".into());
+            return;
+        }
+        if encrypting && *pass != passphrase_confirm.get() {
+            status.set("Passphrases don't match".into());
+            return;
+        }
+        let action = current.clone();
+        clear();
+        match action {
+            PassphrasePrompt::Save(target) => {
+                start_encrypt(&sink, pass, ExportTarget::Save(target));
+            }
+            PassphrasePrompt::Share => start_encrypt(&sink, pass, ExportTarget::Share),
+            PassphrasePrompt::Load { bytes, lenient } => {
+                start_decrypt(&status, bytes, DecryptKey::Passphrase(pass), lenient)
+            }
+            PassphrasePrompt::AgeIdentity { bytes, lenient } => {
+                if let Err(e) = crypto::check_identity(&pass) {
+                    status.set(format!("{e}"));
+                    return;
+                }
+                store_internal_text(AGE_IDENTITY_FILE, &pass);
+                start_decrypt(&status, bytes, DecryptKey::AgeIdentity(pass), lenient)
+            }
+            PassphrasePrompt::CsvColumns { .. } => {}
+        }
+    };
+
+    let mut fields = vec![
+        Text(title).size(13.0).color(Color::WHITE),
+        Space(Modifier::new().height(8.0)),
+        TextFieldEx::new(hint, pass_sig.get(), Modifier::new().fill_max_width())
+            .password()
+            .on_change(move |s| pass_sig.set(s))
+            .build(),
+    ];
+    if encrypting {
+        fields.push(Space(Modifier::new().height(6.0)));
+        fields.push(
+            TextFieldEx::new(
+                "Repeat passphrase",
+                confirm_sig.get(),
+                Modifier::new().fill_max_width(),
+            )
+            .password()
+            .on_change(move |s| confirm_sig.set(s))
+            .build(),
+        );
+    }
+    fields.push(Space(Modifier::new().height(8.0)));
+    fields.push(Row(Modifier::new().fill_max_width()).with_children(vec![
+        styled_button("Cancel", Color::from_hex("#555555"), cancel),
+        Space(Modifier::new().width(8.0)),
+        styled_button("OK", Color::from_hex("#4CAF50"), confirm),
+    ]));
+
+    Column(
+        Modifier::new()
+            .fill_max_width()
+            .padding(12.0)
+            .background(Color::from_hex("#1E1E1E"))
+            .clip_rounded(8.0),
+    )
+    .with_children(fields)
+}
+
+/// Shows the columns guessed for a CSV file and a preview of what they give,
+/// and imports with whatever the user settles on.
+fn csv_columns_dialog(
+    prompt: Signal<Option<PassphrasePrompt>>,
+    bytes: Vec<u8>,
+    mapping: CsvMapping,
+    sink: ImportSink,
+) -> View {
+    let text = String::from_utf8_lossy(&bytes).into_owned();
+    let columns = parsers::csv_columns(&text, &mapping);
+    let preview = parsers::parse_csv(&text, &mapping);
+
+    let mut fields = vec![
+        Text("CSV columns").size(13.0).color(Color::WHITE),
+        Space(Modifier::new().height(6.0)),
+        choice_row(
+            "Delimiter",
+            CSV_DELIMITERS
+                .into_iter()
+                .map(|d| {
+                    let label = if d == '\t' {
+                        "Tab".into()
+                    } else {
+                        d.to_string()
+                    };
+                    let edit = edit_csv_prompt(&prompt, move |bytes, m| {
+                        let text = String::from_utf8_lossy(bytes);
+                        if let Some(new) = parsers::csv_mapping_for(&text, d) {
+                            *m = new;
+                        }
+                    });
+                    choice_chip(label, d == mapping.delimiter, edit)
+                })
+                .collect(),
+        ),
+        Button(
+            Text(if mapping.has_header {
+                "First row is a header: yes"
+            } else {
+                "First row is a header: no"
+            })
+            .size(12.0)
+            .color(Color::from_hex("#AAAAAA")),
+            edit_csv_prompt(&prompt, |_, m| m.has_header = !m.has_header),
+        ),
+    ];
+
+    let column_chips = |selected: Option<usize>, set: SetColumn| {
+        columns
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let edit = edit_csv_prompt(&prompt, move |_, m| set(m, Some(i)));
+                choice_chip(name.clone(), selected == Some(i), edit)
+            })
+            .collect::<Vec<_>>()
+    };
+    fields.push(choice_row(
+        "SSID",
+        column_chips(Some(mapping.ssid), |m, c| m.ssid = c.unwrap_or(m.ssid)),
+    ));
+    let optional: [(&str, Option<usize>, SetColumn); 4] = [
+        ("Password", mapping.pass, |m, c| m.pass = c),
+        ("Security", mapping.security, |m, c| m.security = c),
+        ("Hidden", mapping.hidden, |m, c| m.hidden = c),
+        ("Notes", mapping.notes, |m, c| m.notes = c),
+    ];
+    for (label, selected, set) in optional {
+        let mut chips = vec![choice_chip(
+            "none".into(),
+            selected.is_none(),
+            edit_csv_prompt(&prompt, move |_, m| set(m, None)),
+        )];
+        chips.extend(column_chips(selected, set));
+        fields.push(choice_row(label, chips));
+    }
+
+    let names: Vec<&str> = preview.iter().take(3).map(|c| c.ssid.as_str()).collect();
+    fields.push(
+        Text(match preview.len() {
+            0 => "No networks with these columns".to_string(),
+            n if n > names.len() => format!("{} networks: {}, …", n, names.join(", ")),
+            n => format!("{} networks: {}", n, names.join(", ")),
+        })
+        .size(12.0)
+        .color(Color::from_hex("#AAAAAA")),
+    );
+
+    let cancel = {
+        let (prompt, status) = (prompt.clone(), sink.status.clone());
+        move || {
+            prompt.set(None);
+            status.set("Cancelled".into());
+        }
+    };
+    let confirm = move || {
+        prompt.set(None);
+        let opts = ImportOptions {
+            csv_mapping: Some(&mapping),
+            ..Default::default()
+        };
+        apply_import(&sink, &bytes, opts)
+    };
+    fields.push(Space(Modifier::new().height(8.0)));
+    fields.push(Row(Modifier::new().fill_max_width()).with_children(vec![
+        styled_button("Cancel", Color::from_hex("#555555"), cancel),
+        Space(Modifier::new().width(8.0)),
+        styled_button("Import", Color::from_hex("#4CAF50"), confirm),
+    ]));
+
+    Column(
+        Modifier::new()
+            .fill_max_width()
+            .padding(12.0)
+            .background(Color::from_hex("#1E1E1E"))
+            .clip_rounded(8.0),
+    )
+    .with_children(fields)
+}
+
+/// Points one `CsvMapping` field at a column, or at none.
+type SetColumn = fn(&mut CsvMapping, Option<usize>);
+
+/// Click handler applying `edit` to the mapping of the open CSV prompt.
+fn edit_csv_prompt(
+    prompt: &Signal<Option<PassphrasePrompt>>,
+    edit: impl Fn(&[u8], &mut CsvMapping) + Clone + 'static,
+) -> impl Fn() + Clone + 'static {
+    let prompt = prompt.clone();
+    move || {
+        if let Some(PassphrasePrompt::CsvColumns { bytes, mut mapping }) = prompt.get() {
+            edit(&bytes, &mut mapping);
+            prompt.set(Some(PassphrasePrompt::CsvColumns { bytes, mapping }));
+        }
+    }
+}
+
+fn choice_row(label: &str, chips: Vec<View>) -> View {
+    Column(Modifier::new().fill_max_width().padding(2.0)).with_children(vec![
+        Text(label).size(12.0).color(Color::WHITE),
+        FlowRow(Modifier::new().fill_max_width()).with_children(chips),
+    ])
+}
+
+fn choice_chip<F: Fn() + Clone + 'static>(label: String, selected: bool, action: F) -> View {
+    let bg = if selected {
+        Color::from_hex("#2E7D32")
+    } else {
+        Color::from_hex("#2A2A2A")
+    };
+    Box(Modifier::new().padding(3.0)).with_children(vec![
+        Button(Text(label).size(12.0).color(Color::WHITE), action).modifier(
+            Modifier::new()
+                .padding(6.0)
+                .background(bg)
+                .clip_rounded(6.0),
+        ),
+    ])
+}
+
+/// Where an export built on the worker goes.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum ExportTarget {
+    Save(SaveTarget),
+    Share,
+}
+
+/// Builds the passphrase-encrypted export on the worker: Argon2id takes seconds.
+fn start_encrypt(sink: &ImportSink, passphrase: Zeroizing<String>, target: ExportTarget) {
+    let list = sink.creds.get();
+    let opts = ExportOptions {
+        passphrase: Some(passphrase),
+        source_path: sink.source_path.get(),
+        ..Default::default()
+    };
+    start_task(&sink.status, Task::Encrypt, move |_| {
+        let files = build_export(ExportFormat::Encrypted, &list, ts_secs(), &opts);
+        Outcome::Encrypted(files, target)
+    });
+}
+
+/// Secret for [`start_decrypt`].
+enum DecryptKey {
+    Passphrase(Zeroizing<String>),
+    /// Contents of an age identity file.
+    AgeIdentity(Zeroizing<String>),
+}
+
+/// Decrypts and parses an encrypted import on the worker.
+fn start_decrypt(status: &Signal<String>, bytes: Vec<u8>, key: DecryptKey, lenient: bool) {
+    start_task(status, Task::Decrypt, move |_| {
+        let mut opts = ImportOptions {
+            lenient,
+            ..Default::default()
+        };
+        match &key {
+            DecryptKey::Passphrase(p) => opts.passphrase = Some(p),
+            DecryptKey::AgeIdentity(i) => opts.age_identity = Some(i),
+        }
+        Outcome::Decrypted(parse_import_bytes(&bytes, opts))
+    });
+}
+
+/// Signals a load or import writes its results to.
+#[derive(Clone)]
+struct ImportSink {
+    creds: Signal<Vec<WifiCred>>,
+    status: Signal<String>,
+    json_buf: Signal<String>,
+    source_path: Signal<Option<String>>,
+    rejected: Signal<Vec<RejectedEntry>>,
+    /// Android users seen by the last "Load System".
+    users: Signal<Vec<AndroidUser>>,
+    add_rows: Signal<HashMap<usize, AddState>>,
+}
+
+impl ImportSink {
+    /// Replaces the list. Row states are by index, so they no longer apply.
+    fn set_creds(&self, list: Vec<WifiCred>) {
+        self.creds.set(list);
+        self.add_rows.set(HashMap::new());
+    }
+}
+
+/// Starts `body` on the worker, or says what's already running.
+fn start_task<F>(status: &Signal<String>, task: Task, body: F) -> bool
+where
+    F: FnOnce(&worker::Context) -> Outcome + Send + 'static,
+{
+    match worker::spawn(task, body) {
+        Ok(()) => {
+            status.set(format!("{}...", task.label()));
+            true
+        }
+        Err(busy) => {
+            status.set(format!("Busy: {}", busy.label()));
+            false
+        }
+    }
+}
+
+fn load_system(
+    status: Signal<String>,
+    discover: Signal<bool>,
+    extra_paths: Signal<String>,
+) -> impl Fn() + Clone + 'static {
+    move || {
+        let extra = extra_paths.get();
+        // Typing only updates the signal; the list is saved when it's used.
+        store_internal_text(CONFIG_PATHS_FILE, &extra);
+        let search = config_search(discover.get(), &extra);
+        start_task(&status, Task::LoadSystem, move |_| {
+            Outcome::System(try_read_with_su(&search))
+        });
+    }
+}
+
+/// The default config paths plus the user's, as "Load System" and the
+/// diagnostics search them.
+fn config_search(discover: bool, extra_paths: &str) -> ConfigSearch {
+    let mut search = ConfigSearch {
+        discover,
+        ..Default::default()
+    };
+    search.paths.extend(split_paths(extra_paths));
+    search
+}
+
+/// User-entered search paths: one per line or comma separated.
+fn split_paths(text: &str) -> Vec<String> {
+    text.split(['\n', ','])
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn finish_load_system(sink: &ImportSink, result: anyhow::Result<SystemNetworks>) {
+    match result {
+        Ok(SystemNetworks {
+            networks: mut v,
+            files,
+            users,
+        }) => {
+            v.retain(|c| !c.ssid.is_empty());
+            v.sort_by(|a, b| a.ssid.to_lowercase().cmp(&b.ssid.to_lowercase()));
+            let count = v.len();
+            let sources = files.join(", ");
+            let json = build_json(&v, ts_secs(), Some(&sources));
+            sink.set_creds(v);
+            sink.json_buf.set(json);
+            sink.source_path.set(Some(sources));
+            sink.rejected.set(Vec::new());
+            let user_count = users.len();
+            sink.users.set(users);
+            sink.status.set(format!(
+                "Loaded {} networks from {} file(s), {} user(s)",
+                count,
+                files.len(),
+                user_count
+            ));
+        }
+        Err(e) => {
+            warn!("Load failed: {e:?}");
+            sink.status.set(format!("Failed: {}", e));
+        }
+    }
+}
+
+fn load_file(status: Signal<String>) -> impl Fn() + Clone + 'static {
+    move || {
+        start_task(&status, Task::LoadFile, |ctx| {
+            Outcome::File(find_import_file(ctx))
+        });
+    }
+}
+
+/// First readable `Download/wifi_import.*`; runs on the worker.
+fn find_import_file(ctx: &worker::Context) -> Result<Option<Vec<u8>>, ImportError> {
+    let mut read_error = None;
+    for dir in IMPORT_DIRS {
+        for ext in IMPORT_EXTENSIONS {
+            if ctx.is_cancelled() {
+                return Ok(None);
+            }
+            match su_read(&format!("{}/wifi_import.{}", dir, ext)) {
+                Ok(bytes) => return Ok(Some(bytes)),
+                Err(ImportError::NotFound(_)) => {}
+                Err(e) => {
+                    read_error.get_or_insert(e);
+                }
+            }
+        }
+    }
+    match read_error {
+        Some(e) => Err(e),
+        None => Ok(None),
+    }
+}
+
+fn finish_load_file(
+    sink: &ImportSink,
+    prompt: &Signal<Option<PassphrasePrompt>>,
+    result: Result<Option<Vec<u8>>, ImportError>,
+    lenient: bool,
+) {
+    match result {
+        Ok(Some(bytes)) => import_bytes(sink, prompt, bytes, lenient),
+        Ok(None) => sink.status.set(format!(
+            "File not found: Download/wifi_import.{{{}}}",
+            IMPORT_EXTENSIONS.join(",")
+        )),
+        Err(e) => {
+            warn!("Import read error: {e:?}");
+            sink.status.set(e.to_string());
+        }
+    }
+}
+
+/// Applies whatever the worker reported since the last frame.
+fn drain_worker_events(
+    sink: &ImportSink,
+    prompt: &Signal<Option<PassphrasePrompt>>,
+    tracker: &AddTracker,
+    diagnostics: &Signal<Option<Vec<Check>>>,
+    lenient: bool,
+) {
+    for event in worker::take_events() {
+        match event {
+            Event::NetworkAdded {
+                row,
+                index,
+                total,
+                ssid,
+                error,
+            } => {
+                let mark = if error.is_none() { "✓" } else { "✗" };
+                sink.status.set(format!(
+                    "Adding networks... {}/{} {} {}",
+                    index + 1,
+                    total,
+                    mark,
+                    ssid
+                ));
+                tracker.record(total, row, error);
+            }
+            Event::Cancelled(task) => {
+                if task == Task::ImportAll {
+                    tracker.finish();
+                }
+                if task == Task::WriteDocument {
+                    clear_pending_saves();
+                }
+                sink.status.set(format!("Cancelled: {}", task.label()))
+            }
+            Event::Done(Outcome::System(result)) => finish_load_system(sink, result),
+            Event::Done(Outcome::File(result)) => finish_load_file(sink, prompt, result, lenient),
+            Event::Done(Outcome::Added(ssid, added)) => match added {
+                Ok(_) => sink.status.set(format!("✓ Added '{}'", ssid)),
+                Err(e) => sink.status.set(format!("✗ {}: {}", ssid, e)),
+            },
+            Event::Done(Outcome::Imported(result)) => {
+                tracker.finish();
+                finish_import_all(&sink.status, result)
+            }
+            Event::Done(Outcome::Encrypted(files, target)) => match files {
+                Ok(files) => deliver_export(files, ExportFormat::Encrypted, &sink.status, target),
+                Err(e) => export_failed(&sink.status, e),
+            },
+            Event::Done(Outcome::Decrypted(parsed)) => merge_import(sink, parsed),
+            Event::Done(Outcome::Documents(docs)) => import_documents(sink, prompt, docs, lenient),
+            Event::Done(Outcome::DocumentWritten(uri, written)) => {
+                finish_document_written(&sink.status, uri, written)
+            }
+            Event::Done(Outcome::Diagnostics(checks)) => {
+                let failed = checks
+                    .iter()
+                    .filter(|c| c.state == CheckState::Fail)
+                    .count();
+                sink.status.set(match failed {
+                    0 => "Diagnostics: all checks passed".into(),
+                    n => format!("Diagnostics: {} check(s) failed", n),
+                });
+                diagnostics.set(Some(checks));
+            }
+        }
+    }
+}
+
+fn run_diagnostics(
+    status: Signal<String>,
+    discover: Signal<bool>,
+    extra_paths: Signal<String>,
+) -> impl Fn() + Clone + 'static {
+    move || {
+        let search = config_search(discover.get(), &extra_paths.get());
+        start_task(&status, Task::Diagnostics, move |_| {
+            Outcome::Diagnostics(diagnostics::run(&search))
+        });
+    }
+}
+
+fn diagnostics_view(
+    diagnostics: Signal<Option<Vec<Check>>>,
+    status: Signal<String>,
+    discover: Signal<bool>,
+    extra_paths: Signal<String>,
+) -> View {
+    let Some(checks) = diagnostics.get() else {
+        return Column(Modifier::new().fill_max_width().padding(4.0)).with_children(vec![Button(
+            Text("Run root diagnostics")
+                .size(12.0)
+                .color(Color::from_hex("#AAAAAA")),
+            run_diagnostics(status, discover, extra_paths),
+        )]);
+    };
+
+    let mut rows: Vec<View> = checks
+        .iter()
+        .map(|c| {
+            let (mark, color) = match c.state {
+                CheckState::Pass => ("✓", "#69F0AE"),
+                CheckState::Fail => ("✗", "#FF5252"),
+                CheckState::Info => ("•", "#AAAAAA"),
+            };
+            Text(format!("{} {}: {}", mark, c.name, c.detail))
+                .size(12.0)
+                .color(Color::from_hex(color))
+        })
+        .collect();
+
+    let report = diagnostics::report(&checks, ts_secs());
+    let save = {
+        let (report, status) = (report.clone(), status.clone());
+        move || {
+            let Some(app) = ANDROID_APP.get() else {
+                return;
+            };
+            let name = format!("wifi_exporter_diagnostics_{}.txt", ts_secs());
+            match write_file_via_mediastore(app, &name, "text/plain", report.as_bytes()) {
+                Ok(Some(_)) => status.set(format!("Saved: {}", name)),
+                Ok(None) => status.set("Save failed".into()),
+                Err(e) => {
+                    warn!("Diagnostics save error: {e:?}");
+                    status.set(format!("Save failed: {}", e));
+                }
+            }
+        }
+    };
+    let share = {
+        let status = status.clone();
+        move || {
+            let Some(app) = ANDROID_APP.get() else {
+                return;
+            };
+            let name = format!("wifi_exporter_diagnostics_{}.txt", ts_secs());
+            let shared = share_files(
+                app,
+                "WiFi Exporter diagnostics",
+                "text/plain",
+                &[(&name, report.as_bytes())],
+            );
+            // The report holds no passwords, so text is fine without a FileProvider.
+            let result = match shared {
+                Ok(false) => share_text(app, "WiFi Exporter diagnostics", &report),
+                other => other.map(|_| ()),
+            };
+            if let Err(e) = result {
+                warn!("Diagnostics share error: {e:?}");
+                status.set("Share failed".into());
+            }
+        }
+    };
+    rows.push(Space(Modifier::new().height(6.0)));
+    rows.push(Row(Modifier::new().fill_max_width()).with_children(vec![
+        styled_button("Save report", Color::from_hex("#455A64"), save),
+        Space(Modifier::new().width(8.0)),
+        styled_button("Share report", Color::from_hex("#455A64"), share),
+        Space(Modifier::new().width(8.0)),
+        styled_button("Close", Color::from_hex("#2A2A2A"), move || {
+            diagnostics.set(None)
+        }),
+    ]));
+    Column(
+        Modifier::new()
+            .fill_max_width()
+            .padding(8.0)
+            .background(Color::from_hex("#1E1E1E"))
+            .clip_rounded(8.0),
+    )
+    .with_children(rows)
+}
+
+fn busy_row() -> View {
+    let Some(task) = worker::running() else {
+        return Space(Modifier::new().height(0.0));
+    };
+    Row(Modifier::new().fill_max_width().padding(4.0)).with_children(vec![
+        Text(format!("{}…", task.label()))
+            .size(12.0)
+            .color(Color::from_hex("#AAAAAA"))
+            .modifier(Modifier::new().weight(1.0)),
+        Button(
+            Text("Cancel").size(12.0).color(Color::WHITE),
+            worker::cancel,
+        )
+        .modifier(
+            Modifier::new()
+                .padding(6.0)
+                .background(Color::from_hex("#B71C1C"))
+                .clip_rounded(6.0),
+        ),
+    ])
+}
+
+/// Imports one file's contents, asking for the passphrase first if it's encrypted.
+fn import_bytes(
+    sink: &ImportSink,
+    prompt: &Signal<Option<PassphrasePrompt>>,
+    bytes: Vec<u8>,
+    lenient: bool,
+) {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(256)]).into_owned();
+    if crypto::is_encrypted_export(&head) {
+        prompt.set(Some(PassphrasePrompt::Load { bytes, lenient }));
+        sink.status
+            .set("Encrypted file: enter its passphrase".into());
+    } else if crypto::is_age_export(&head) {
+        match load_age_identity() {
+            Some(identity) => start_decrypt(
+                &sink.status,
+                bytes,
+                DecryptKey::AgeIdentity(Zeroizing::new(identity)),
+                lenient,
+            ),
+            None => {
+                prompt.set(Some(PassphrasePrompt::AgeIdentity { bytes, lenient }));
+                sink.status.set("age file: paste your identity".into());
+            }
+        }
+    } else if let Some(mapping) = csv_mapping_of(&bytes) {
+        prompt.set(Some(PassphrasePrompt::CsvColumns { bytes, mapping }));
+        sink.status.set("CSV file: check the columns".into());
+    } else {
+        let opts = ImportOptions {
+            lenient,
+            ..Default::default()
+        };
+        apply_import(sink, &bytes, opts)
+    }
+}
+
+/// The guessed columns when `bytes` would be imported as a plain CSV file.
+fn csv_mapping_of(bytes: &[u8]) -> Option<CsvMapping> {
+    parsers::detect_reader(bytes).filter(|(r, _)| r.id == "csv")?;
+    parsers::detect_csv_mapping(std::str::from_utf8(bytes).ok()?)
+}
+
+fn pick_files(status: Signal<String>) -> impl Fn() + Clone + 'static {
+    move || {
+        let Some(app) = ANDROID_APP.get() else {
+            return;
+        };
+        if let Err(e) = android_jni::open_documents(app, true) {
+            warn!("Document picker failed: {e:?}");
+            status.set(format!("Could not open the file picker: {}", e));
+        }
+    }
+}
+
+/// Handles picker results delivered by the forwarding activity. Reading a picked
+/// file takes the worker, so the rest wait until it's free.
+fn drain_activity_results(sink: &ImportSink) {
+    while worker::running().is_none() {
+        let Some(result) = android_jni::next_activity_result() else {
+            return;
+        };
+        if result.request_code == android_jni::REQUEST_CREATE_DOCUMENT {
+            finish_picked_save(&sink.status, result.ok, result.uris.into_iter().next());
+            continue;
+        }
+        if result.request_code == android_jni::REQUEST_SHARE {
+            // The share sheet is closed, so the attachments have been read.
+            if let Some(app) = ANDROID_APP.get()
+                && let Err(e) = android_jni::cleanup_shared_files(app)
+            {
+                warn!("Share cleanup failed: {e:?}");
+            }
+            continue;
+        }
+        if result.request_code != android_jni::REQUEST_OPEN_DOCUMENT {
+            continue;
+        }
+        if !result.ok || result.uris.is_empty() {
+            sink.status.set("No file picked".into());
+            continue;
+        }
+        read_documents(&sink.status, result.uris);
+    }
+}
+
+/// Reads `content://` URIs on the worker; providers may have to fetch them first.
+fn read_documents(status: &Signal<String>, uris: Vec<String>) -> bool {
+    let Some(app) = ANDROID_APP.get().cloned() else {
+        return false;
+    };
+    start_task(status, Task::ReadDocuments, move |ctx| {
+        Outcome::Documents(read_uris(&app, ctx, uris))
+    })
+}
+
+fn read_uris(
+    app: &AndroidApp,
+    ctx: &worker::Context,
+    uris: Vec<String>,
+) -> Vec<(String, anyhow::Result<Vec<u8>>)> {
+    let mut docs = Vec::with_capacity(uris.len());
+    for uri in uris {
+        if ctx.is_cancelled() {
+            break;
+        }
+        let bytes = android_jni::read_content_uri(app, &uri);
+        docs.push((uri, bytes));
+    }
+    docs
+}
+
+/// Imports each document the worker read; every file is merged as it comes, so
+/// the list ends up with all of them.
+fn import_documents(
+    sink: &ImportSink,
+    prompt: &Signal<Option<PassphrasePrompt>>,
+    docs: Vec<(String, anyhow::Result<Vec<u8>>)>,
+    lenient: bool,
+) {
+    for (uri, bytes) in docs {
+        match bytes {
+            Ok(bytes) => import_bytes(sink, prompt, bytes, lenient),
+            Err(e) => {
+                warn!("Reading {uri} failed: {e:?}");
+                sink.status.set(format!("Could not read {}: {}", uri, e));
+            }
+        }
+    }
+}
+
+/// Reads the file or text another app opened or shared with us on the worker.
+/// Most launches carry neither, so this doesn't touch the status line.
+fn read_incoming_intent() {
+    let Some(app) = ANDROID_APP.get().cloned() else {
+        return;
+    };
+    let spawned = worker::spawn(Task::ReadDocuments, move |ctx| {
+        Outcome::Documents(match android_jni::incoming_intent(&app) {
+            Ok(Some(IncomingIntent::Uris(uris))) => read_uris(&app, ctx, uris),
+            Ok(Some(IncomingIntent::Text(text))) => {
+                vec![("the shared text".into(), Ok(text.into_bytes()))]
+            }
+            Ok(None) => Vec::new(),
+            Err(e) => vec![("the shared file".into(), Err(e))],
+        })
+    });
+    if let Err(busy) = spawned {
+        warn!("Shared file dropped while busy: {}", busy.label());
+    }
+}
+
+fn apply_import(sink: &ImportSink, bytes: &[u8], opts: ImportOptions<'_>) {
+    merge_import(sink, parse_import_bytes(bytes, opts))
+}
+
+/// Merges a parsed import into the list and reports what happened.
+fn merge_import(sink: &ImportSink, parsed: Result<ParsedImport, ImportError>) {
+    match parsed {
+        Ok(parsed) => {
+            let import_count = parsed.networks.len();
+            let skipped = parsed.rejected.len();
+            for r in &parsed.rejected {
+                warn!("Skipped entry #{}: {}", r.index, r.reason);
+            }
+            let merged = merge_creds(sink.creds.get(), parsed.networks);
+
+            let total = merged.len();
+            let json = build_json(&merged, ts_secs(), sink.source_path.get().as_deref());
+            sink.set_creds(merged);
+            sink.json_buf.set(json);
+            sink.rejected.set(parsed.rejected);
+            if skipped == 0 {
+                sink.status
+                    .set(format!("Imported {}. Total: {}", import_count, total));
+            } else {
+                sink.status.set(format!(
+                    "Imported {}, skipped {} bad entries. Total: {}",
+                    import_count, skipped, total
+                ));
+            }
+        }
+        Err(e) => {
+            warn!("Import error: {e:?}");
+            sink.rejected.set(Vec::new());
+            sink.status.set(format!("Import failed: {}", e));
+        }
+    }
+}
+
+/// Per-command su timeouts the toggle cycles through, in seconds.
+const SU_TIMEOUT_CHOICES: [u64; 4] = [15, 30, 60, 120];
+
+/// Limits on a whole "Import All" run the second toggle cycles through, in minutes.
+const SU_OVERALL_CHOICES: [u64; 4] = [5, 10, 30, 60];
+
+/// The choice after `current`, wrapping around.
+fn next_choice(choices: &[u64], current: u64) -> u64 {
+    choices
+        .iter()
+        .copied()
+        .find(|&c| c > current)
+        .unwrap_or(choices[0])
+}
+
+fn su_timeout_toggle(su_timeout: Signal<u64>, su_overall: Signal<u64>) -> View {
+    let current = su_timeout.get();
+    let next = next_choice(&SU_TIMEOUT_CHOICES, current);
+    let overall = su_overall.get();
+    let next_overall = next_choice(&SU_OVERALL_CHOICES, overall);
+    Column(Modifier::new().fill_max_width().padding(4.0)).with_children(vec![
+        Button(
+            Text(format!("su timeout: {} s per command", current))
+                .size(12.0)
+                .color(Color::from_hex("#AAAAAA")),
+            move || {
+                parsers::set_su_timeouts(SuTimeouts {
+                    command: Duration::from_secs(next),
+                    ..parsers::su_timeouts()
+                });
+                su_timeout.set(next);
+            },
+        ),
+        Button(
+            Text(format!("Import All stops after {} min", overall))
+                .size(12.0)
+                .color(Color::from_hex("#AAAAAA")),
+            move || {
+                parsers::set_su_timeouts(SuTimeouts {
+                    overall: Duration::from_secs(next_overall * 60),
+                    ..parsers::su_timeouts()
+                });
+                su_overall.set(next_overall);
+            },
+        ),
+    ])
+}
+
+/// "Load System" search settings: `find` discovery and extra paths or globs.
+fn config_search_options(discover: Signal<bool>, extra_paths: Signal<String>) -> View {
+    let on = discover.get();
+    let label = if on {
+        "Discover config files: on (find under /data/misc*)"
+    } else {
+        "Discover config files: off"
+    };
+    let rejected = split_paths(&extra_paths.get())
+        .into_iter()
+        .filter(|p| !parsers::is_path_pattern(p))
+        .count();
+    let sig = extra_paths.clone();
+    let mut children = vec![
+        Button(
+            Text(label).size(12.0).color(Color::from_hex("#AAAAAA")),
+            move || discover.set(!on),
+        ),
+        TextFieldEx::new(
+            "Extra config paths (globs ok, comma separated)",
+            extra_paths.get(),
+            Modifier::new().fill_max_width(),
+        )
+        .on_change(move |s| sig.set(s))
+        .on_submit(|s| store_internal_text(CONFIG_PATHS_FILE, &s))
+        .build(),
+    ];
+    if rejected > 0 {
+        children.push(
+            Text(format!(
+                "{} path(s) ignored: only letters, digits and /._-+@*?[] are allowed",
+                rejected
+            ))
+            .size(12.0)
+            .color(Color::from_hex("#FF5252")),
+        );
+    }
+    Column(Modifier::new().fill_max_width().padding(4.0)).with_children(children)
+}
+
+fn lenient_toggle(lenient: Signal<bool>) -> View {
+    let on = lenient.get();
+    let label = if on {
+        "Lenient JSON import: on (skip bad entries)"
+    } else {
+        "Lenient JSON import: off"
+    };
+    Column(Modifier::new().fill_max_width().padding(4.0)).with_children(vec![Button(
+        Text(label).size(12.0).color(Color::from_hex("#AAAAAA")),
+        move || lenient.set(!on),
+    )])
+}
+
+/// Entries a lenient import skipped, so the source file can be fixed.
+fn rejected_list(rejected: Vec<RejectedEntry>) -> View {
+    if rejected.is_empty() {
+        return Space(Modifier::new().height(0.0));
+    }
+    let mut rows = vec![
+        Text(format!("Skipped {} entries:", rejected.len()))
+            .size(13.0)
+            .color(Color::from_hex("#FFB74D")),
+    ];
+    let hidden = rejected.len().saturating_sub(MAX_REJECTED_SHOWN);
+    for r in rejected.into_iter().take(MAX_REJECTED_SHOWN) {
+        rows.push(
+            Text(format!("#{} {}", r.index, r.reason))
+                .size(12.0)
+                .color(Color::from_hex("#FF8888")),
+        );
+        rows.push(Text(r.snippet).size(11.0).color(Color::from_hex("#888888")));
+    }
+    if hidden > 0 {
+        rows.push(
+            Text(format!("…and {} more (see log)", hidden))
+                .size(12.0)
+                .color(Color::from_hex("#888888")),
+        );
+    }
+    Column(
+        Modifier::new()
+            .fill_max_width()
+            .padding(8.0)
+            .background(Color::from_hex("#1E1E1E"))
+            .clip_rounded(8.0),
+    )
+    .with_children(rows)
+}
+
+/// Merges imported networks into the loaded list; when both hold the same network
+/// for the same user (see [`WifiCred::network_key`]) the loaded entry wins.
+fn merge_creds(existing: Vec<WifiCred>, imported: Vec<WifiCred>) -> Vec<WifiCred> {
+    let mut map = HashMap::new();
+    for c in imported {
+        map.insert((c.user, c.network_key()), c);
+    }
+    for c in existing {
+        map.insert((c.user, c.network_key()), c);
+    }
+
+    let mut merged: Vec<WifiCred> = map.into_values().collect();
+    merged.retain(|c| !c.ssid.is_empty());
+    merged.sort_by_key(|c| (c.ssid.to_lowercase(), c.ssid.clone(), c.user));
+    merged
+}
+
+fn import_to_system(
+    creds: Signal<Vec<WifiCred>>,
+    status: Signal<String>,
+    tracker: AddTracker,
+    convert_suggestions: Signal<bool>,
+) -> impl Fn() + Clone + 'static {
+    move || {
+        if get_api_level() < MIN_IMPORT_API {
+            status.set(format!(
+                "Import requires Android 11+ (API {}+)",
+                MIN_IMPORT_API
+            ));
+            return;
+        }
+
+        let all = creds.get();
+        if all.is_empty() {
+            status.set("No networks loaded".into());
+            return;
+        }
+        let convert = convert_suggestions.get();
+        // `rows[i]` is where the i-th imported network sits in the loaded list.
+        let (rows, list): (Vec<usize>, Vec<WifiCred>) = all
+            .into_iter()
+            .enumerate()
+            .filter(|(_, c)| convert || c.suggested_by.is_none())
+            .unzip();
+        if list.is_empty() {
+            status.set("Only app suggestions loaded; turn on \"Add app suggestions\"".into());
+            return;
+        }
+
+        let total = list.len();
+        let pending: HashMap<usize, AddState> =
+            rows.iter().map(|&row| (row, AddState::Pending)).collect();
+        let started = start_task(&status, Task::ImportAll, move |ctx| {
+            let result = su_import_all(
+                &list,
+                |r| {
+                    let row = rows[r.index];
+                    ctx.network_added(row, r.index, total, r.ssid.clone(), r.error.clone())
+                },
+                || ctx.is_cancelled(),
+            );
+            Outcome::Imported(result)
+        });
+        if started {
+            tracker.rows.set(pending);
+            tracker.progress.set(Some((0, total)));
+        }
+    }
+}
+
+/// Order of the network list.
+#[derive(Clone, Copy, PartialEq)]
+enum SortOrder {
+    Name,
+    /// Most recently connected first; unknown last.
+    LastConnected,
+}
+
+/// Which networks the list shows, by days since they last connected. Networks
+/// Android says never connected count as unused for over a year; those with no
+/// history at all (not read from `WifiConfigStore.xml`) only show under `All`.
+#[derive(Clone, Copy, PartialEq)]
+enum AgeFilter {
+    All,
+    Within90Days,
+    WithinYear,
+    OlderThanYear,
+}
+
+impl AgeFilter {
+    const ALL: [AgeFilter; 4] = [
+        AgeFilter::All,
+        AgeFilter::Within90Days,
+        AgeFilter::WithinYear,
+        AgeFilter::OlderThanYear,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            AgeFilter::All => "All",
+            AgeFilter::Within90Days => "Used ≤ 90 d",
+            AgeFilter::WithinYear => "Used ≤ 1 y",
+            AgeFilter::OlderThanYear => "Unused > 1 y",
+        }
+    }
+
+    fn keeps(self, days: Option<u64>) -> bool {
+        match (self, days) {
+            (AgeFilter::All, _) => true,
+            (AgeFilter::Within90Days, Some(d)) => d <= 90,
+            (AgeFilter::WithinYear, Some(d)) => d <= 365,
+            (AgeFilter::OlderThanYear, Some(d)) => d > 365,
+            _ => false,
+        }
+    }
+}
+
+/// The networks to list, each with its index in `creds`.
+fn shown_networks(
+    creds: Vec<WifiCred>,
+    sort: SortOrder,
+    filter: AgeFilter,
+    now: u64,
+) -> Vec<(usize, WifiCred)> {
+    let mut shown: Vec<(usize, WifiCred)> = creds
+        .into_iter()
+        .enumerate()
+        .filter(|(_, c)| filter.keeps(c.days_since_connected(now)))
+        .collect();
+    if sort == SortOrder::LastConnected {
+        // Stable, so equal ages keep name order; never-connected sorts with unknown.
+        shown.sort_by_key(|(_, c)| c.days_since_connected(now).unwrap_or(u64::MAX));
+    }
+    shown
+}
+
+/// Age filter chips, the sort toggle and, while filtering, a button that drops
+/// every network the filter hides (e.g. years-old ones before migrating).
+/// Networks without history are hidden by every filter but never dropped, since
+/// nothing says they're stale.
+fn list_controls(
+    sink: &ImportSink,
+    sort_order: Signal<SortOrder>,
+    age_filter: Signal<AgeFilter>,
+    shown: usize,
+) -> View {
+    let creds = sink.creds.get();
+    let total = creds.len();
+    if total == 0 {
+        return Space(Modifier::new().height(0.0));
+    }
+    let now = ts_secs();
+    let unknown = creds
+        .iter()
+        .filter(|c| c.days_since_connected(now).is_none())
+        .count();
+    let current = age_filter.get();
+    let mut chips: Vec<View> = AgeFilter::ALL
+        .into_iter()
+        .map(|filter| {
+            let sig = age_filter.clone();
+            let bg = if filter == current {
+                Color::from_hex("#2E7D32")
+            } else {
+                Color::from_hex("#2A2A2A")
+            };
+            Box(Modifier::new().padding(3.0)).with_children(vec![
+                Button(
+                    Text(filter.label()).size(12.0).color(Color::WHITE),
+                    move || sig.set(filter),
+                )
+                .modifier(
+                    Modifier::new()
+                        .padding(6.0)
+                        .background(bg)
+                        .clip_rounded(6.0),
+                ),
+            ])
+        })
+        .collect();
+    let sort = sort_order.get();
+    let (sort_label, next) = match sort {
+        SortOrder::Name => ("Sort: name", SortOrder::LastConnected),
+        SortOrder::LastConnected => ("Sort: last connected", SortOrder::Name),
+    };
+    chips.push(Box(Modifier::new().padding(3.0)).with_children(vec![Button(
+        Text(sort_label).size(12.0).color(Color::from_hex("#AAAAAA")),
+        move || sort_order.set(next),
+    )]));
+
+    let mut children = vec![FlowRow(Modifier::new().fill_max_width()).with_children(chips)];
+    if current != AgeFilter::All {
+        children.push(
+            Text(format!("{} of {} networks shown", shown, total))
+                .size(12.0)
+                .color(Color::from_hex("#AAAAAA")),
+        );
+        if shown + unknown < total {
+            let label = if unknown == 0 {
+                format!("Keep only these {}", shown)
+            } else {
+                format!("Keep these {} and {} with no history", shown, unknown)
+            };
+            children.push(
+                Button(Text(label).size(12.0).color(Color::WHITE), {
+                    let sink = sink.clone();
+                    move || {
+                        let now = ts_secs();
+                        let mut list = sink.creds.get();
+                        list.retain(|c| match c.days_since_connected(now) {
+                            None => true,
+                            days => current.keeps(days),
+                        });
+                        sink.status
+                            .set(format!("Removed {} networks", total - list.len()));
+                        sink.set_creds(list);
+                    }
+                })
+                .modifier(
+                    Modifier::new()
+                        .padding(6.0)
+                        .background(Color::from_hex("#B71C1C"))
+                        .clip_rounded(6.0),
+                ),
+            );
+        }
+    }
+    Column(Modifier::new().fill_max_width().padding(4.0)).with_children(children)
+}
+
+/// "Last connected 12 days ago", "Never connected", or empty when unknown.
+fn last_connected_label(c: &WifiCred, now: u64) -> String {
+    match c.days_since_connected(now) {
+        None => String::new(),
+        Some(u64::MAX) => "Never connected".into(),
+        Some(0) => "Last connected today".into(),
+        Some(1) => "Last connected yesterday".into(),
+        Some(d) => format!("Last connected {} days ago", d),
+    }
+}
+
+/// Per-row state of the last "Import All to System", keyed by index into the
+/// loaded list; SSIDs aren't unique. Cleared when the list changes.
+#[derive(Clone, PartialEq)]
+enum AddState {
+    Pending,
+    Added,
+    Failed(String),
+}
+
+#[derive(Clone)]
+struct AddTracker {
+    rows: Signal<HashMap<usize, AddState>>,
+    /// (done, total) while a bulk import runs.
+    progress: Signal<Option<(usize, usize)>>,
+}
+
+impl AddTracker {
+    fn record(&self, total: usize, row: usize, error: Option<String>) {
+        let mut rows = self.rows.get();
+        // Gone if the list changed since the import started.
+        let Some(state) = rows.get_mut(&row) else {
+            return;
+        };
+        *state = error.map_or(AddState::Added, AddState::Failed);
+        let done = rows.values().filter(|s| **s != AddState::Pending).count();
+        self.rows.set(rows);
+        self.progress.set(Some((done.min(total), total)));
+    }
+
+    /// Stops the bar; rows never reached go back to having no icon.
+    fn finish(&self) {
+        let mut rows = self.rows.get();
+        rows.retain(|_, s| *s != AddState::Pending);
+        self.rows.set(rows);
+        self.progress.set(None);
+    }
+}
+
+fn progress_bar(progress: Option<(usize, usize)>) -> View {
+    let Some((done, total)) = progress.filter(|&(_, t)| t > 0) else {
+        return Space(Modifier::new().height(0.0));
+    };
+    // Weights must be positive, so an empty side gets a sliver.
+    let filled = (done as f32).max(0.001);
+    let rest = ((total - done) as f32).max(0.001);
+    Column(Modifier::new().fill_max_width().padding(4.0)).with_children(vec![
+        Text(format!("{} / {}", done, total))
+            .size(12.0)
+            .color(Color::from_hex("#AAAAAA")),
+        Space(Modifier::new().height(4.0)),
+        Row(Modifier::new()
+            .fill_max_width()
+            .height(6.0)
+            .background(Color::from_hex("#333333"))
+            .clip_rounded(3.0))
+        .with_children(vec![
+            Box(Modifier::new()
+                .weight(filled)
+                .height(6.0)
+                .background(Color::from_hex("#4CAF50"))),
+            Box(Modifier::new().weight(rest).height(6.0)),
+        ]),
+    ])
+}
+
+fn finish_import_all(status: &Signal<String>, result: Result<ImportSummary, SuError>) {
+    let summary = match result {
+        Ok(summary) => summary,
+        Err(e) => {
+            warn!("Bulk import failed: {e:?}");
+            status.set(e.to_string());
+            return;
+        }
+    };
+    if summary.unfinished > 0 {
+        // Only the overall limit ends a run early; a cancel never reaches here.
+        status.set(format!(
+            "Stopped at the time limit: added {}, failed {}, {} not finished. Check Wi‑Fi settings for the last one.",
+            summary.success,
+            summary.failed,
+            summary.unfinished
+        ));
+    } else if summary.failed == 0 {
+        status.set(format!("✓ Added {} networks", summary.success));
+    } else {
+        let first_err = summary
+            .errors
+            .first()
+            .map(|(s, e)| format!("{}: {}", s, e))
+            .unwrap_or_default();
+        status.set(format!(
+            "Added {}, failed {}. {}",
+            summary.success, summary.failed, first_err
+        ));
+    }
+}
+
+fn save_export(
+    creds: Signal<Vec<WifiCred>>,
+    status: Signal<String>,
+    settings: ExportSettings,
+    source_path: Signal<Option<String>>,
+    prompt: Signal<Option<PassphrasePrompt>>,
+) -> impl Fn(SaveTarget) + Clone + 'static {
+    move |target| {
+        let list = creds.get();
+        if list.is_empty() {
+            status.set("Nothing to save".into());
+            return;
+        }
+        let format = settings.format.get();
+        if format == ExportFormat::Encrypted {
+            prompt.set(Some(PassphrasePrompt::Save(target)));
+            return;
+        }
+        let opts = settings.options(&list, source_path.get());
+        write_export(&list, format, &opts, &status, target);
+    }
+}
+
+fn write_export(
+    list: &[WifiCred],
+    format: ExportFormat,
+    opts: &ExportOptions,
+    status: &Signal<String>,
+    target: SaveTarget,
+) {
+    match build_export(format, list, ts_secs(), opts) {
+        Ok(files) => save_files(files, status, target),
+        Err(e) => export_failed(status, e),
+    }
+}
+
+fn export_failed(status: &Signal<String>, e: anyhow::Error) {
+    warn!("Export error: {e:?}");
+    status.set(format!("Export failed: {}", e));
+}
+
+fn deliver_export(
+    files: Vec<ExportFile>,
+    format: ExportFormat,
+    status: &Signal<String>,
+    target: ExportTarget,
+) {
+    match target {
+        ExportTarget::Save(target) => save_files(files, status, target),
+        ExportTarget::Share => share_built(files, format, status),
+    }
+}
+
+fn save_files(files: Vec<ExportFile>, status: &Signal<String>, target: SaveTarget) {
+    let Some(app) = ANDROID_APP.get() else {
+        return;
+    };
+    if target == SaveTarget::Picked {
+        if let Ok(mut pending) = PENDING_SAVES.lock() {
+            *pending = files.into_iter().rev().collect();
+        }
+        request_next_save(app, status);
+        return;
+    }
+    let total = files.len();
+    let mut saved = Vec::new();
+    for file in files {
+        match write_file_via_mediastore(app, &file.name, file.mime, file.contents.as_bytes()) {
+            Ok(Some(_)) => saved.push(file.name),
+            Ok(None) => {}
+            Err(e) => warn!("Save error for {}: {e:?}", file.name),
+        }
+    }
+    match saved.as_slice() {
+        [] => status.set("Save failed".into()),
+        [one] if total == 1 => status.set(format!("Saved: {}", one)),
+        _ => status.set(format!(
+            "Saved {} of {} files to Downloads",
+            saved.len(),
+            total
+        )),
+    }
+}
+
+/// Opens "Save as" for the next pending file, if any.
+fn request_next_save(app: &AndroidApp, status: &Signal<String>) {
+    let next = PENDING_SAVES
+        .lock()
+        .ok()
+        .and_then(|p| p.last().map(|f| (f.name.clone(), f.mime)));
+    let Some((name, mime)) = next else {
+        return;
+    };
+    if let Err(e) = android_jni::create_document(app, &name, mime) {
+        warn!("Save-as picker failed: {e:?}");
+        clear_pending_saves();
+        status.set(format!("Could not open the save dialog: {}", e));
+    }
+}
+
+fn clear_pending_saves() {
+    if let Ok(mut pending) = PENDING_SAVES.lock() {
+        pending.clear();
+    }
+}
+
+/// Writes the pending file to the URI the user picked, on the worker: the
+/// provider may be a cloud or USB one.
+fn finish_picked_save(status: &Signal<String>, ok: bool, uri: Option<String>) {
+    let Some(app) = ANDROID_APP.get().cloned() else {
+        return;
+    };
+    let uri = match uri {
+        Some(uri) if ok => uri,
+        _ => {
+            clear_pending_saves();
+            status.set("Save cancelled".into());
+            return;
+        }
+    };
+    let Some(file) = PENDING_SAVES.lock().ok().and_then(|mut p| p.pop()) else {
+        return;
+    };
+    let started = start_task(status, Task::WriteDocument, move |_| {
+        let written = android_jni::write_content_uri(&app, &uri, file.contents.as_bytes());
+        if let Err(e) = &written {
+            warn!("Save error for {}: {e:?}", file.name);
+        }
+        Outcome::DocumentWritten(uri, written)
+    });
+    if !started {
+        clear_pending_saves();
+    }
+}
+
+/// Reports a "Save as" write, then asks for the next file.
+fn finish_document_written(status: &Signal<String>, uri: String, written: anyhow::Result<()>) {
+    match written {
+        Ok(()) => status.set(format!("Saved: {}", uri)),
+        Err(e) => {
+            clear_pending_saves();
+            status.set(format!("Save failed: {}", e));
+            return;
+        }
+    }
+    if let Some(app) = ANDROID_APP.get() {
+        request_next_save(app, status);
+    }
+}
+
+fn share_json(
+    creds: Signal<Vec<WifiCred>>,
+    status: Signal<String>,
+    settings: ExportSettings,
+    source_path: Signal<Option<String>>,
+    prompt: Signal<Option<PassphrasePrompt>>,
+) -> impl Fn() + Clone + 'static {
+    move || {
+        let list = creds.get();
+        if list.is_empty() {
+            status.set("Nothing to share".into());
+            return;
+        }
+        let format = settings.format.get();
+        if format == ExportFormat::Encrypted {
+            prompt.set(Some(PassphrasePrompt::Share));
+            return;
+        }
+        let opts = settings.options(&list, source_path.get());
+        share_export(&list, format, &opts, &status);
+    }
+}
+
+/// Shares the export as file attachments from the app cache.
+fn share_export(
+    list: &[WifiCred],
+    format: ExportFormat,
+    opts: &ExportOptions,
+    status: &Signal<String>,
+) {
+    match build_export(format, list, ts_secs(), opts) {
+        Ok(files) => share_built(files, format, status),
+        Err(e) => export_failed(status, e),
+    }
+}
+
+fn share_built(files: Vec<ExportFile>, format: ExportFormat, status: &Signal<String>) {
+    let Some(app) = ANDROID_APP.get() else {
+        return;
+    };
+    let attachments: Vec<(&str, &[u8])> = files
+        .iter()
+        .map(|f| (f.name.as_str(), f.contents.as_bytes()))
+        .collect();
+    match share_files(app, "WiFi Passwords", format.mime(), &attachments) {
+        Ok(true) => {}
+        // Never fall back to text: it ends up in clipboard histories.
+        Ok(false) => status.set("Sharing needs the Gradle build; use Save instead".into()),
+        Err(e) => {
+            warn!("Share error: {e:?}");
+            status.set(format!("Share failed: {}", e));
+        }
+    }
+}
+
+/// "User 10 (Work profile)", or "shared" for networks outside per-user stores.
+fn user_label(user: Option<u32>, users: &[AndroidUser]) -> String {
+    let Some(id) = user else {
+        return "shared".into();
+    };
+    match users.iter().find(|u| u.id == id) {
+        Some(u) if !u.name.is_empty() => format!("User {} ({})", id, u.name),
+        _ => format!("User {}", id),
+    }
+}
+
+/// Whether "Import All" turns app-suggested networks into saved ones. Hidden
+/// when none are loaded.
+fn suggestions_toggle(creds: &[WifiCred], convert: Signal<bool>) -> View {
+    let count = creds.iter().filter(|c| c.suggested_by.is_some()).count();
+    if count == 0 {
+        return Space(Modifier::new().height(0.0));
+    }
+    let on = convert.get();
+    let label = if on {
+        format!(
+            "Add app suggestions: on ({} saved as regular networks)",
+            count
+        )
+    } else {
+        format!("Add app suggestions: off ({} skipped)", count)
+    };
+    Column(Modifier::new().fill_max_width().padding(4.0)).with_children(vec![Button(
+        Text(label).size(12.0).color(Color::from_hex("#AAAAAA")),
+        move || convert.set(!on),
+    )])
+}
+
+/// Shown once "Load System" found more than one user: "Import All" and "+"
+/// always add networks for the current user, never for a profile or another
+/// user, since `cmd wifi` has no way to pick one.
+fn current_user_note(users: &[AndroidUser]) -> View {
+    if users.len() < 2 {
+        return Space(Modifier::new().height(0.0));
+    }
+    Text("Import adds networks for the current user only, not its profiles or other users")
+        .size(12.0)
+        .color(Color::from_hex("#AAAAAA"))
+        .modifier(Modifier::new().padding(4.0))
+}
+
+/// Where a row came from: user and file for "Load System", plus the
+/// suggesting app.
+fn origin_label(c: &WifiCred, users: &[AndroidUser]) -> String {
+    let mut parts = Vec::new();
+    if let Some(source) = &c.source {
+        parts.push(format!("{} · {}", user_label(c.user, users), source));
+    }
+    if let Some(package) = &c.suggested_by {
+        parts.push(format!("suggested by {}", package));
+    }
+    parts.join(" · ")
+}
+
+fn network_list(
+    creds: Vec<(usize, WifiCred)>,
+    now: u64,
+    add_rows: &HashMap<usize, AddState>,
+    status: Signal<String>,
+    can_import: bool,
+    users: &[AndroidUser],
+) -> View {
+    let scroll_state = remember_scroll_state("network_list");
+
+    let rows: Vec<View> = if creds.is_empty() {
+        vec![
+            Text("No networks loaded")
+                .size(14.0)
+                .color(Color::from_hex("#666666"))
+                .modifier(Modifier::new().padding(16.0)),
+        ]
+    } else {
+        creds
+            .into_iter()
+            .map(|(row, c)| {
+                let cred = c.clone();
+                let status_signal = status.clone();
+
+                let add_action = move || {
+                    if !can_import {
+                        status_signal.set("Import requires Android 11+".into());
+                        return;
+                    }
+                    let cred = cred.clone();
+                    // The su prompt alone can take seconds.
+                    start_task(&status_signal, Task::AddNetwork, move |_| {
+                        let added = su_add_network(&cred);
+                        Outcome::Added(cred.ssid, added)
+                    });
+                };
+
+                let btn_color = if can_import {
+                    Color::from_hex("#4CAF50")
+                } else {
+                    Color::from_hex("#444444")
+                };
+
+                let pass_display = c.pass.as_deref().unwrap_or("<no password>");
+                let (icon, icon_color) = match add_rows.get(&row) {
+                    Some(AddState::Pending) => ("…", "#888888"),
+                    Some(AddState::Added) => ("✓", "#69F0AE"),
+                    Some(AddState::Failed(_)) => ("✗", "#FF5252"),
+                    None => ("", "#000000"),
+                };
+
+                Row(Modifier::new()
+                    .fill_max_width()
+                    .padding(8.0)
+                    .background(Color::from_hex("#1E1E1E"))
+                    .clip_rounded(8.0))
+                .with_children(vec![
+                    Column(Modifier::new().weight(1.0).padding(4.0)).with_children(vec![
+                        Text(&c.ssid).size(15.0).color(Color::WHITE),
+                        Text(pass_display)
+                            .size(12.0)
+                            .color(Color::from_hex("#AAAAAA")),
+                        Text(origin_label(&c, users))
+                            .size(10.0)
+                            .color(Color::from_hex("#777777")),
+                        Text(last_connected_label(&c, now))
+                            .size(10.0)
+                            .color(Color::from_hex("#777777")),
+                    ]),
+                    Text(icon)
+                        .size(16.0)
+                        .color(Color::from_hex(icon_color))
+                        .modifier(Modifier::new().padding(8.0)),
+                    Button(Text("+").size(16.0).color(Color::WHITE), add_action).modifier(
+                        Modifier::new()
+                            .size(40.0, 40.0)
+                            .background(btn_color)
+                            .clip_rounded(15.0),
+                    ),
+                    Space(Modifier::new().width(20.0)),
+                ])
+                .modifier(Modifier::new().padding(2.0))
+            })
+            .collect()
+    };
+
+    ScrollArea(
+        Modifier::new().fill_max_size(),
+        scroll_state,
+        Column(Modifier::new().fill_max_width()).with_children(rows),
+    )
+}
+
+fn ts_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn android_main(android_app: AndroidApp) {
+    android_logger::init_once(android_logger::Config::default().with_max_level(LevelFilter::Info));
+    let _ = ANDROID_APP.set(android_app.clone());
+    // A share that was still open when the app died leaves its files behind.
+    if let Err(e) = android_jni::cleanup_shared_files(&android_app) {
+        warn!("Share cleanup failed: {e:?}");
+    }
+    let _ = run_android_app(
+        android_app,
+        app as fn(&mut Scheduler, &RenderContext) -> View,
+    );
+}
@@ -0,0 +1,269 @@
+//! Passphrase-encrypted export container.
+//!
+//! Layout (all integers little-endian), ASCII-armored with base64 so the file
+//! survives text shares and copy-paste:
+//!
+//! ```text
+//! magic "WIFIXENC" | version u8 | kdf u8 (1 = Argon2id)
+//! m_cost u32 (KiB) | t_cost u32 | p_cost u32 | salt_len u8 | salt
+//! nonce [24] | XChaCha20-Poly1305 ciphertext + tag
+//! ```
+//!
+//! Everything before the ciphertext is authenticated as associated data.
+//!
+//! Exports can instead be encrypted to age X25519 recipients (`age1…`), producing
+//! a standard ASCII-armored age file that `age -d -i key.txt` can open.
+
+use age::armor::{ArmoredReader, ArmoredWriter, Format as ArmorFormat};
+use anyhow::Context;
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as B64;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use std::io::{Read, Write};
+use zeroize::Zeroizing;
+
+const MAGIC: &[u8; 8] = b"WIFIXENC";
+const VERSION: u8 = 1;
+const KDF_ARGON2ID: u8 = 1;
+
+const ARMOR_BEGIN: &str = "-----BEGIN WIFI EXPORTER ENCRYPTED-----";
+const ARMOR_END: &str = "-----END WIFI EXPORTER ENCRYPTED-----";
+
+const M_COST_KIB: u32 = 64 * 1024;
+const T_COST: u32 = 3;
+const P_COST: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Refuse to derive keys with parameters that would exhaust a phone's memory
+/// or keep it busy for minutes.
+const MAX_M_COST_KIB: u32 = 512 * 1024;
+const MAX_T_COST: u32 = 16;
+const MAX_P_COST: u32 = 16;
+
+pub const MIME: &str = "application/octet-stream";
+pub const EXTENSION: &str = "wifienc";
+
+const AGE_ARMOR_BEGIN: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+const AGE_BINARY_MAGIC: &str = "age-encryption.org/v1";
+
+pub const AGE_EXTENSION: &str = "age";
+
+/// Argon2id key, wiped from memory when dropped. Slow on purpose: call it off
+/// the UI thread.
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    m: u32,
+    t: u32,
+    p: u32,
+) -> anyhow::Result<Zeroizing<[u8; 32]>> {
+    let params = Params::new(m, t, p, Some(32)).map_err(|e| anyhow::anyhow!("KDF params: {e}"))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(|e| anyhow::anyhow!("Key derivation failed: {e}"))?;
+    Ok(key)
+}
+
+/// Encrypts `plaintext` (normally `build_json` output) and returns the armored container.
+pub fn encrypt_export(plaintext: &[u8], passphrase: &str) -> anyhow::Result<String> {
+    if passphrase.is_empty() {
+        return Err(anyhow::anyhow!("Empty passphrase"));
+    }
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let mut header = Vec::with_capacity(64);
+    header.extend_from_slice(MAGIC);
+    header.push(VERSION);
+    header.push(KDF_ARGON2ID);
+    header.extend_from_slice(&M_COST_KIB.to_le_bytes());
+    header.extend_from_slice(&T_COST.to_le_bytes());
+    header.extend_from_slice(&P_COST.to_le_bytes());
+    header.push(SALT_LEN as u8);
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&nonce);
+
+    let key = derive_key(passphrase, &salt, M_COST_KIB, T_COST, P_COST)?;
+    let cipher = XChaCha20Poly1305::new((&*key).into());
+    let ct = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: &header,
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+
+    let mut blob = header;
+    blob.extend_from_slice(&ct);
+    Ok(armor(&blob))
+}
+
+fn armor(blob: &[u8]) -> String {
+    let b64 = B64.encode(blob);
+    let mut out = String::with_capacity(b64.len() + b64.len() / 64 + 80);
+    out.push_str(ARMOR_BEGIN);
+    out.push('\n');
+    for line in b64.as_bytes().chunks(64) {
+        out.push_str(std::str::from_utf8(line).unwrap_or_default());
+        out.push('\n');
+    }
+    out.push_str(ARMOR_END);
+    out.push('\n');
+    out
+}
+
+fn dearmor(text: &str) -> anyhow::Result<Vec<u8>> {
+    let start = text.find(ARMOR_BEGIN).context("Missing armor header")? + ARMOR_BEGIN.len();
+    let end = text[start..]
+        .find(ARMOR_END)
+        .map(|e| start + e)
+        .context("Missing armor footer")?;
+    let b64: String = text[start..end]
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    B64.decode(b64).context("Corrupted armor")
+}
+
+pub fn is_encrypted_export(text: &str) -> bool {
+    text.trim_start().starts_with(ARMOR_BEGIN)
+}
+
+/// Decrypts an armored container. A wrong passphrase and a tampered file look the same.
+pub fn decrypt_export(text: &str, passphrase: &str) -> anyhow::Result<Vec<u8>> {
+    let blob = dearmor(text)?;
+    let mut r = blob.as_slice();
+    let mut take = |n: usize| -> anyhow::Result<&[u8]> {
+        if r.len() < n {
+            return Err(anyhow::anyhow!("Truncated encrypted export"));
+        }
+        let (head, rest) = r.split_at(n);
+        r = rest;
+        Ok(head)
+    };
+    let u32_at = |b: &[u8]| u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+
+    if take(MAGIC.len())? != MAGIC {
+        return Err(anyhow::anyhow!("Not a WiFi Exporter encrypted file"));
+    }
+    let version = take(1)?[0];
+    if version != VERSION {
+        return Err(anyhow::anyhow!(
+            "Unsupported encrypted export version {} (this app reads {})",
+            version,
+            VERSION
+        ));
+    }
+    let kdf = take(1)?[0];
+    if kdf != KDF_ARGON2ID {
+        return Err(anyhow::anyhow!("Unsupported key derivation {}", kdf));
+    }
+    let m = u32_at(take(4)?);
+    let t = u32_at(take(4)?);
+    let p = u32_at(take(4)?);
+    if m > MAX_M_COST_KIB || t > MAX_T_COST || p > MAX_P_COST {
+        return Err(anyhow::anyhow!("Key derivation parameters too large"));
+    }
+    let salt_len = take(1)?[0] as usize;
+    let salt = take(salt_len)?.to_vec();
+    let nonce = *XNonce::from_slice(take(NONCE_LEN)?);
+    let header_len = blob.len() - r.len();
+    let (header, ct) = blob.split_at(header_len);
+
+    let key = derive_key(passphrase, &salt, m, t, p)?;
+    XChaCha20Poly1305::new((&*key).into())
+        .decrypt(
+            &nonce,
+            Payload {
+                msg: ct,
+                aad: header,
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("Wrong passphrase or corrupted file"))
+}
+
+/// Splits a recipients list (whitespace, comma or newline separated; `#` starts a
+/// comment) into individual `age1…` strings.
+pub fn split_recipients(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|l| l.split('#').next().unwrap_or_default())
+        .flat_map(|l| l.split(|c: char| c == ',' || c.is_whitespace()))
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_recipients(recipients: &[String]) -> anyhow::Result<Vec<age::x25519::Recipient>> {
+    if recipients.is_empty() {
+        return Err(anyhow::anyhow!("No age recipients configured"));
+    }
+    recipients
+        .iter()
+        .map(|r| {
+            r.parse::<age::x25519::Recipient>()
+                .map_err(|e| anyhow::anyhow!("Bad age recipient '{}': {}", r, e))
+        })
+        .collect()
+}
+
+/// Encrypts `plaintext` to every recipient and returns an armored age file.
+pub fn encrypt_to_recipients(plaintext: &[u8], recipients: &[String]) -> anyhow::Result<String> {
+    let recipients = parse_recipients(recipients)?;
+    let encryptor =
+        age::Encryptor::with_recipients(recipients.iter().map(|r| r as &dyn age::Recipient))?;
+
+    let mut out = Vec::new();
+    let armored = ArmoredWriter::wrap_output(&mut out, ArmorFormat::AsciiArmor)?;
+    let mut writer = encryptor.wrap_output(armored)?;
+    writer.write_all(plaintext)?;
+    writer.finish()?.finish()?;
+    String::from_utf8(out).context("Armored age output is not UTF-8")
+}
+
+pub fn is_age_export(text: &str) -> bool {
+    let t = text.trim_start();
+    t.starts_with(AGE_ARMOR_BEGIN) || t.starts_with(AGE_BINARY_MAGIC)
+}
+
+/// Checks that `identity_file` holds at least one usable age key.
+pub fn check_identity(identity_file: &str) -> anyhow::Result<()> {
+    parse_identity(identity_file).map(|_| ())
+}
+
+fn parse_identity(identity_file: &str) -> anyhow::Result<Vec<Box<dyn age::Identity>>> {
+    let identities = age::IdentityFile::from_buffer(identity_file.as_bytes())
+        .context("Unreadable age identity file")?
+        .into_identities()?;
+    if identities.is_empty() {
+        return Err(anyhow::anyhow!("No keys in the age identity file"));
+    }
+    Ok(identities)
+}
+
+/// Decrypts an age file (armored or binary) with the keys in an age identity file.
+pub fn decrypt_with_identity(data: &[u8], identity_file: &str) -> anyhow::Result<Vec<u8>> {
+    let identities = parse_identity(identity_file)?;
+    let decryptor = age::Decryptor::new_buffered(ArmoredReader::new(data))?;
+    let mut reader = decryptor
+        .decrypt(identities.iter().map(|i| i.as_ref() as &dyn age::Identity))
+        .map_err(|e| match e {
+            age::DecryptError::NoMatchingKeys => {
+                anyhow::anyhow!("File is not encrypted to this age identity")
+            }
+            e => anyhow::anyhow!("age decryption failed: {e}"),
+        })?;
+    let mut plain = Vec::new();
+    reader.read_to_end(&mut plain)?;
+    Ok(plain)
+}
+
+#[cfg(test)]
+mod tests;
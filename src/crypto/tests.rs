@@ -0,0 +1,122 @@
+use super::*;
+use age::secrecy::ExposeSecret;
+use sha2::{Digest, Sha256};
+
+const VECTORS: &[&str] = &[
+    "x25519",
+    "armor",
+    "armor_crlf",
+    "armor_no_eol",
+    "x25519_multiple_recipients",
+    "x25519_grease",
+    "stream_empty_payload",
+];
+
+struct Vector {
+    identity: String,
+    payload: String,
+    body: Vec<u8>,
+}
+
+fn vector(name: &str) -> Vector {
+    let path = format!("{}/tests/fixtures/age/{name}", env!("CARGO_MANIFEST_DIR"));
+    let data = std::fs::read(&path).unwrap();
+    let split = data.windows(2).position(|w| w == b"\n\n").unwrap();
+    let header = std::str::from_utf8(&data[..split]).unwrap();
+    let field = |key: &str| {
+        header
+            .lines()
+            .find_map(|l| l.strip_prefix(key)?.strip_prefix(": "))
+            .unwrap_or_else(|| panic!("{name}: no {key}"))
+            .to_string()
+    };
+    assert_eq!(field("expect"), "success", "{name}");
+    Vector {
+        identity: field("identity"),
+        payload: field("payload"),
+        body: data[split + 2..].to_vec(),
+    }
+}
+
+#[test]
+fn age_reference_vectors_decrypt() {
+    for name in VECTORS {
+        let v = vector(name);
+        let plain =
+            decrypt_with_identity(&v.body, &v.identity).unwrap_or_else(|e| panic!("{name}: {e:#}"));
+        let digest: String = Sha256::digest(&plain)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        assert_eq!(digest, v.payload, "{name}");
+    }
+}
+
+#[test]
+fn age_export_opens_with_reference_decryptor() {
+    let identity = age::x25519::Identity::generate();
+    let recipient = identity.to_public().to_string();
+    let armored = encrypt_to_recipients(b"{\"networks\":[]}", &[recipient]).unwrap();
+    assert!(is_age_export(&armored));
+
+    let decryptor = age::Decryptor::new(ArmoredReader::new(armored.as_bytes())).unwrap();
+    let mut reader = decryptor
+        .decrypt(std::iter::once(&identity as &dyn age::Identity))
+        .unwrap();
+    let mut plain = Vec::new();
+    reader.read_to_end(&mut plain).unwrap();
+    assert_eq!(plain, b"{\"networks\":[]}");
+}
+
+#[test]
+fn age_wrong_identity_is_reported() {
+    let v = vector("x25519");
+    let other = age::x25519::Identity::generate();
+    let err = decrypt_with_identity(&v.body, other.to_string().expose_secret()).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("not encrypted to this age identity")
+    );
+}
+
+#[test]
+fn check_identity_rejects_non_keys() {
+    assert!(check_identity(&vector("x25519").identity).is_ok());
+    assert!(check_identity("").is_err());
+    assert!(check_identity("age1notasecret").is_err());
+}
+
+#[test]
+fn bad_recipient_is_rejected() {
+    let err = encrypt_to_recipients(b"x", &["age1bogus".into()]).unwrap_err();
+    assert!(err.to_string().contains("age1bogus"));
+    assert!(encrypt_to_recipients(b"x", &[]).is_err());
+}
+
+#[test]
+fn split_recipients_handles_separators_and_comments() {
+    let text = "age1a, age1b\nage1c # laptop\n# old: age1d\n\tage1e";
+    assert_eq!(split_recipients(text), ["age1a", "age1b", "age1c", "age1e"]);
+}
+
+#[test]
+fn passphrase_container_round_trips() {
+    let armored = encrypt_export(b"secret", "correct horse").unwrap();
+    assert!(is_encrypted_export(&armored));
+    assert_eq!(
+        decrypt_export(&armored, "correct horse").unwrap(),
+        b"secret"
+    );
+    assert!(decrypt_export(&armored, "wrong").is_err());
+}
+
+#[test]
+fn oversized_kdf_parameters_are_refused() {
+    let armored = encrypt_export(b"secret", "pw").unwrap();
+    let mut blob = dearmor(&armored).unwrap();
+    // magic | version | kdf | m_cost | t_cost | p_cost
+    let p_cost = MAGIC.len() + 2 + 8;
+    blob[p_cost..p_cost + 4].copy_from_slice(&(MAX_P_COST + 1).to_le_bytes());
+    let err = decrypt_export(&armor(&blob), "pw").unwrap_err();
+    assert!(err.to_string().contains("too large"));
+}
@@ -0,0 +1,270 @@
+//! Root capability probe, so "root denied" and "config not found" can be told
+//! apart. Runs on the worker; every check is independent and never aborts the rest.
+
+use crate::error::SuError;
+use crate::parsers::{
+    ConfigSearch, expand_user, find_config_files, get_api_level, get_device_model, is_path_pattern,
+    run_su_cmd,
+};
+use std::process::Command;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckState {
+    Pass,
+    Fail,
+    /// Reported for context; neither good nor bad.
+    Info,
+}
+
+impl CheckState {
+    pub fn tag(self) -> &'static str {
+        match self {
+            CheckState::Pass => "PASS",
+            CheckState::Fail => "FAIL",
+            CheckState::Info => "INFO",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Check {
+    pub name: String,
+    pub state: CheckState,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, state: CheckState, detail: impl Into<String>) -> Self {
+        Check {
+            name: name.into(),
+            state,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Output of a non-root shell command, trimmed; `None` if it couldn't run.
+fn sh(cmd: &str) -> Option<String> {
+    Command::new("sh")
+        .arg("-c")
+        .arg(cmd)
+        .output()
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+}
+
+/// Runs every check; the config path checks cover the same `search` "Load
+/// System" uses.
+pub fn run(search: &ConfigSearch) -> Vec<Check> {
+    let mut checks = vec![Check::new(
+        "Device",
+        CheckState::Info,
+        format!(
+            "{} (API {})",
+            get_device_model().unwrap_or_else(|| "unknown model".into()),
+            get_api_level()
+        ),
+    )];
+
+    match sh("command -v su").filter(|p| !p.is_empty()) {
+        Some(path) => checks.push(Check::new("su binary", CheckState::Pass, path)),
+        None => {
+            checks.push(Check::new(
+                "su binary",
+                CheckState::Fail,
+                "not found on PATH",
+            ));
+        }
+    }
+
+    let rooted = match run_su_cmd("id -u") {
+        Ok(uid) if uid == "0" => {
+            checks.push(Check::new(
+                "Root grant",
+                CheckState::Pass,
+                "su runs as uid 0",
+            ));
+            true
+        }
+        Ok(uid) => {
+            checks.push(Check::new(
+                "Root grant",
+                CheckState::Fail,
+                format!("su runs as uid {}", uid),
+            ));
+            false
+        }
+        Err(e) => {
+            checks.push(Check::new("Root grant", CheckState::Fail, e.to_string()));
+            false
+        }
+    };
+
+    checks.push(root_solution(rooted));
+
+    let selinux = if rooted {
+        run_su_cmd("getenforce").ok()
+    } else {
+        sh("getenforce")
+    };
+    checks.push(Check::new(
+        "SELinux",
+        CheckState::Info,
+        selinux
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| "unknown".into()),
+    ));
+
+    if rooted {
+        checks.extend(config_paths(search));
+        checks.extend(cmd_wifi());
+    }
+    checks
+}
+
+/// Magisk, KernelSU and APatch each keep a directory under `/data/adb`.
+fn root_solution(rooted: bool) -> Check {
+    if !rooted {
+        return Check::new("Root solution", CheckState::Info, "unknown (no root)");
+    }
+    let probe =
+        "for d in magisk ksu ap; do [ -e /data/adb/$d ] && echo $d; done; su -v 2>/dev/null";
+    root_solution_check(&run_su_cmd(probe).unwrap_or_default())
+}
+
+fn root_solution_check(out: &str) -> Check {
+    let mut names = Vec::new();
+    let mut version = None;
+    for line in out.lines() {
+        match line.trim() {
+            "magisk" => names.push("Magisk"),
+            "ksu" => names.push("KernelSU"),
+            "ap" => names.push("APatch"),
+            other if !other.is_empty() => version = Some(other.to_string()),
+            _ => {}
+        }
+    }
+    let mut detail = if names.is_empty() {
+        "unrecognised".to_string()
+    } else {
+        names.join(" + ")
+    };
+    if let Some(v) = version {
+        detail.push_str(&format!(" (su -v: {})", v));
+    }
+    Check::new("Root solution", CheckState::Info, detail)
+}
+
+/// Existence and readability of each path in `search`, default and user-added,
+/// plus any file discovery finds, with globs expanded (an unmatched glob shows
+/// up as missing).
+fn config_paths(search: &ConfigSearch) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let mut patterns = Vec::new();
+    for p in &search.paths {
+        if is_path_pattern(p) {
+            patterns.extend(expand_user(p, None));
+        } else {
+            checks.push(Check::new(
+                p.as_str(),
+                CheckState::Fail,
+                "unsupported characters; Load System skips it",
+            ));
+        }
+    }
+    if search.discover {
+        let discover = ConfigSearch {
+            paths: Vec::new(),
+            discover: true,
+        };
+        match find_config_files(&discover, None) {
+            Ok(found) => {
+                for f in found {
+                    if !patterns.contains(&f) {
+                        patterns.push(f);
+                    }
+                }
+            }
+            Err(e) => checks.push(Check::new(
+                "Config discovery",
+                CheckState::Fail,
+                e.to_string(),
+            )),
+        }
+    }
+    if patterns.is_empty() {
+        return checks;
+    }
+    let mut script = String::new();
+    for p in patterns {
+        script.push_str(&format!(
+            "for f in {p}; do if [ ! -e \"$f\" ]; then s=missing; elif head -c 1 \"$f\" >/dev/null 2>&1; then s=\"readable $(wc -c < \"$f\")\"; else s=unreadable; fi; echo \"$s\t$f\"; done\n"
+        ));
+    }
+    match run_su_cmd(&script) {
+        Ok(out) => checks.extend(config_path_checks(&out)),
+        Err(e) => checks.push(Check::new("Config paths", CheckState::Fail, e.to_string())),
+    }
+    checks
+}
+
+/// One check per `<status>\t<path>` line of the [`config_paths`] script.
+fn config_path_checks(out: &str) -> Vec<Check> {
+    out.lines()
+        .filter_map(|line| line.trim().split_once('\t'))
+        .map(|(status, path)| {
+            let (state, detail) = match status.strip_prefix("readable ") {
+                Some(bytes) => (
+                    CheckState::Pass,
+                    format!("readable, {} bytes", bytes.trim()),
+                ),
+                // Older layouts are expected to be missing on newer Android.
+                None if status == "missing" => (CheckState::Info, "missing".to_string()),
+                None => (CheckState::Fail, status.to_string()),
+            };
+            Check::new(path, state, detail)
+        })
+        .collect()
+}
+
+fn cmd_wifi() -> Vec<Check> {
+    let help = match run_su_cmd("cmd wifi help 2>&1") {
+        Ok(h) => h,
+        Err(SuError::CommandFailed { message, .. }) => message,
+        Err(e) => return vec![Check::new("cmd wifi", CheckState::Fail, e.to_string())],
+    };
+    cmd_wifi_checks(&help)
+}
+
+fn cmd_wifi_checks(help: &str) -> Vec<Check> {
+    ["add-network", "list-networks"]
+        .into_iter()
+        .map(|sub| {
+            if help.contains(sub) {
+                Check::new(format!("cmd wifi {}", sub), CheckState::Pass, "supported")
+            } else {
+                Check::new(
+                    format!("cmd wifi {}", sub),
+                    CheckState::Fail,
+                    "not supported (needs Android 11+)",
+                )
+            }
+        })
+        .collect()
+}
+
+/// Plain-text report for bug reports. Contains no passwords or SSIDs.
+pub fn report(checks: &[Check], stamp: u64) -> String {
+    let mut out = format!(
+        "WiFi Exporter {} diagnostics\nGenerated: {} (unix time)\n\n",
+        env!("CARGO_PKG_VERSION"),
+        stamp
+    );
+    for c in checks {
+        out.push_str(&format!("[{}] {}: {}\n", c.state.tag(), c.name, c.detail));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,73 @@
+use super::*;
+
+#[test]
+fn config_path_states() {
+    let out = "readable 2048\t/data/misc/apexdata/com.android.wifi/WifiConfigStore.xml\n\
+               missing\t/data/misc/wifi/WifiConfigStore.xml\n\
+               unreadable\t/data/vendor/wifi/wpa/wpa_supplicant.conf\n\
+               \n\
+               garbage line\n";
+    let checks = config_path_checks(out);
+    let got: Vec<(&str, CheckState, &str)> = checks
+        .iter()
+        .map(|c| (c.name.as_str(), c.state, c.detail.as_str()))
+        .collect();
+    assert_eq!(
+        got,
+        [
+            (
+                "/data/misc/apexdata/com.android.wifi/WifiConfigStore.xml",
+                CheckState::Pass,
+                "readable, 2048 bytes"
+            ),
+            (
+                "/data/misc/wifi/WifiConfigStore.xml",
+                CheckState::Info,
+                "missing"
+            ),
+            (
+                "/data/vendor/wifi/wpa/wpa_supplicant.conf",
+                CheckState::Fail,
+                "unreadable"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn cmd_wifi_support() {
+    let checks = cmd_wifi_checks(
+        "Wi-Fi (wifi) commands:\n  add-network <ssid> open|owe|wpa2|wpa3 [<passphrase>]\n",
+    );
+    assert_eq!(checks[0].name, "cmd wifi add-network");
+    assert_eq!(checks[0].state, CheckState::Pass);
+    assert_eq!(checks[1].name, "cmd wifi list-networks");
+    assert_eq!(checks[1].state, CheckState::Fail);
+}
+
+#[test]
+fn root_solution_names() {
+    let check = root_solution_check("magisk\n27.0:MAGISK:R\n");
+    assert_eq!(check.detail, "Magisk (su -v: 27.0:MAGISK:R)");
+    assert_eq!(root_solution_check("").detail, "unrecognised");
+}
+
+#[test]
+fn report_lists_every_check() {
+    let checks = vec![
+        Check::new("su binary", CheckState::Pass, "/system/bin/su"),
+        Check::new("Root grant", CheckState::Fail, "root access denied"),
+        Check::new("SELinux", CheckState::Info, "Enforcing"),
+    ];
+    let report = report(&checks, 1_760_000_000);
+    assert_eq!(
+        report,
+        format!(
+            "WiFi Exporter {} diagnostics\nGenerated: 1760000000 (unix time)\n\n\
+             [PASS] su binary: /system/bin/su\n\
+             [FAIL] Root grant: root access denied\n\
+             [INFO] SELinux: Enforcing\n",
+            env!("CARGO_PKG_VERSION")
+        )
+    );
+}
@@ -0,0 +1,195 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Why an `su` invocation failed.
+#[derive(Debug)]
+pub enum SuError {
+    /// No `su` binary on PATH: the device isn't rooted.
+    SuNotFound,
+    /// The root manager refused, or the user dismissed its prompt.
+    RootDenied,
+    /// Killed after the configured timeout.
+    TimedOut(Duration),
+    /// su ran, but the command exited non-zero. `message` is its stderr (or stdout).
+    CommandFailed { code: Option<i32>, message: String },
+}
+
+impl fmt::Display for SuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SuError::SuNotFound => {
+                write!(f, "Root not available: no su binary found on this device")
+            }
+            SuError::RootDenied => write!(
+                f,
+                "Root access denied: allow WiFi Exporter in your root manager, then retry"
+            ),
+            SuError::TimedOut(after) => write!(
+                f,
+                "su timed out after {}s: answer the root prompt or raise the su timeout",
+                after.as_secs()
+            ),
+            SuError::CommandFailed {
+                code: Some(code),
+                message,
+            } => write!(f, "Command failed (exit {}): {}", code, message),
+            SuError::CommandFailed {
+                code: None,
+                message,
+            } => write!(f, "Command failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SuError {}
+
+/// Why an import file could not be read or understood.
+#[derive(Debug)]
+pub enum ImportError {
+    Io {
+        path: String,
+        message: String,
+    },
+    PermissionDenied(String),
+    NotFound(String),
+    /// su itself failed (not rooted, denied, timed out) before the file could be read.
+    Root(SuError),
+    /// Syntax error; `line`/`column` are 1-based, 0 when unknown.
+    Malformed {
+        format: &'static str,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// Well-formed, but a value has the wrong shape. `path` is like `networks[3].ssid`.
+    SchemaMismatch {
+        path: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    UnsupportedVersion {
+        found: u64,
+        supported: u32,
+    },
+    /// A recognised format or feature this app can't read.
+    Unsupported(String),
+    ChecksumMismatch,
+    DecryptionFailed(String),
+    NoNetworks(&'static str),
+}
+
+impl ImportError {
+    pub fn malformed_json(format: &'static str, e: &serde_json::Error) -> Self {
+        ImportError::Malformed {
+            format,
+            line: e.line(),
+            column: e.column(),
+            message: strip_position(e),
+        }
+    }
+
+    /// Malformed input where the failure is at byte `offset` of `text`.
+    pub fn malformed_at(format: &'static str, text: &str, offset: usize, message: &str) -> Self {
+        let before = &text[..offset.min(text.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count()
+            + 1;
+        ImportError::Malformed {
+            format,
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn schema(e: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let path = e.path().to_string();
+        let inner = e.into_inner();
+        ImportError::SchemaMismatch {
+            path,
+            line: inner.line(),
+            column: inner.column(),
+            message: strip_position(&inner),
+        }
+    }
+
+    pub fn decryption(e: anyhow::Error) -> Self {
+        ImportError::DecryptionFailed(e.to_string())
+    }
+}
+
+/// serde_json appends " at line X column Y"; we print the position ourselves.
+fn strip_position(e: &serde_json::Error) -> String {
+    let msg = e.to_string();
+    match msg.rfind(" at line ") {
+        Some(i) => msg[..i].to_string(),
+        None => msg,
+    }
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io { path, message } => write!(f, "Could not read {}: {}", path, message),
+            ImportError::PermissionDenied(path) => write!(f, "Permission denied: {}", path),
+            ImportError::NotFound(path) => write!(f, "Not found: {}", path),
+            ImportError::Root(e) => write!(f, "{}", e),
+            ImportError::Malformed {
+                format,
+                line,
+                column,
+                message,
+            } => {
+                if *line > 0 {
+                    write!(
+                        f,
+                        "Malformed {} at line {}, column {}: {}",
+                        format, line, column, message
+                    )
+                } else {
+                    write!(f, "Malformed {}: {}", format, message)
+                }
+            }
+            ImportError::SchemaMismatch {
+                path,
+                line,
+                column,
+                message,
+            } => {
+                let field = if path.is_empty() || path == "." {
+                    "top level"
+                } else {
+                    path
+                };
+                if *line > 0 {
+                    write!(
+                        f,
+                        "Bad value at {} (line {}, column {}): {}",
+                        field, line, column, message
+                    )
+                } else {
+                    write!(f, "Bad value at {}: {}", field, message)
+                }
+            }
+            ImportError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "Export format version {} is newer than this app supports ({}); update WiFi Exporter",
+                found, supported
+            ),
+            ImportError::Unsupported(why) => write!(f, "{}", why),
+            ImportError::ChecksumMismatch => {
+                write!(f, "Checksum mismatch: the file was modified or truncated")
+            }
+            ImportError::DecryptionFailed(why) => write!(f, "Decryption failed: {}", why),
+            ImportError::NoNetworks(what) => write!(f, "No Wi‑Fi networks found in {}", what),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
@@ -1,36 +1,461 @@
+use crate::crypto;
+use crate::error::{ImportError, SuError};
 use anyhow::Context;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as B64;
 use log::{info, warn};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::process::Command;
-use std::sync::OnceLock;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, OnceLock, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub struct WifiCred {
     pub ssid: String,
+    /// Raw SSID bytes (uppercase hex) when they aren't valid UTF-8; `ssid` then
+    /// only shows them, with U+FFFD for the undecodable parts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssid_hex: Option<String>,
     #[serde(alias = "password")]
     pub pass: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security: Option<Security>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Device file the network was read from by "Load System".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Android user whose config store held the network; `None` for the shared store.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<u32>,
+    /// Package that added the network through the suggestion API; `None` for saved networks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggested_by: Option<String>,
+    /// History recorded by Android's config store; `None` for other sources.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<NetworkStats>,
 }
 
-fn shell_escape(s: &str) -> String {
+impl WifiCred {
+    /// Security type as recorded, or guessed from whether a password is present.
+    pub fn security(&self) -> Security {
+        self.security.unwrap_or(match self.pass.as_deref() {
+            Some(p) if !p.is_empty() => Security::Wpa2,
+            _ => Security::Open,
+        })
+    }
+
+    /// The SSID as the radio sees it.
+    pub fn ssid_bytes(&self) -> Vec<u8> {
+        self.ssid_hex
+            .as_deref()
+            .and_then(hex_decode)
+            .unwrap_or_else(|| self.ssid.as_bytes().to_vec())
+    }
+
+    /// What Android treats as one saved network: the exact SSID bytes plus the
+    /// security type. `Home` and `home`, or an open and a WPA2 `Cafe`, are two.
+    pub fn network_key(&self) -> (Vec<u8>, Security) {
+        (self.ssid_bytes(), self.security())
+    }
+
+    /// Whole days since the last connection at `now` (Unix seconds); `u64::MAX`
+    /// if Android says it never connected, `None` when unknown.
+    pub fn days_since_connected(&self, now: u64) -> Option<u64> {
+        let stats = self.stats.as_ref()?;
+        match (stats.last_connected, stats.has_ever_connected) {
+            (Some(ms), _) if ms > 0 => Some(now.saturating_sub(ms / 1000) / 86_400),
+            (_, Some(false)) => Some(u64::MAX),
+            _ => None,
+        }
+    }
+}
+
+/// Who created and last changed a saved network, and when it was last used.
+/// Every field is optional; which ones exist depends on the Android version.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NetworkStats {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator_uid: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_update_uid: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_update_name: Option<String>,
+    /// As Android wrote it (`time=MM-dd HH:mm:ss.SSS` on older releases).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creation_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_time: Option<String>,
+    /// Unix time in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_connected: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_ever_connected: Option<bool>,
+    /// `NetworkStatus` selection state, e.g. `NETWORK_SELECTION_ENABLED`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection_status: Option<String>,
+    /// Config key of the network the user preferred over this one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_choice: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Security {
+    Open,
+    Owe,
+    Wep,
+    Wpa2,
+    Wpa3,
+    Eap,
+}
+
+impl Security {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Security::Open => "open",
+            Security::Owe => "owe",
+            Security::Wep => "wep",
+            Security::Wpa2 => "wpa2",
+            Security::Wpa3 => "wpa3",
+            Security::Eap => "eap",
+        }
+    }
+
+    /// Lenient parse of user-entered security names (CSV cells, vault fields).
+    pub fn from_label(s: &str) -> Option<Self> {
+        match s
+            .trim()
+            .to_ascii_lowercase()
+            .replace(['-', '_', ' '], "")
+            .as_str()
+        {
+            "" => None,
+            "open" | "none" | "nopass" => Some(Security::Open),
+            "owe" | "enhancedopen" => Some(Security::Owe),
+            "wep" => Some(Security::Wep),
+            "wpa" | "wpa2" | "wpapsk" | "wpa2psk" | "psk" | "psk2" => Some(Security::Wpa2),
+            "wpa3" | "sae" | "wpa3sae" | "wpa3personal" => Some(Security::Wpa3),
+            "eap" | "wpaeap" | "wpa2eap" | "enterprise" | "8021x" => Some(Security::Eap),
+            _ => None,
+        }
+    }
+
+    /// Maps a ConfigStore `ConfigKey` suffix or wpa_supplicant `key_mgmt` value.
+    fn from_key_mgmt(km: &str) -> Option<Self> {
+        let km = km.to_ascii_uppercase();
+        if km.contains("SAE") {
+            Some(Security::Wpa3)
+        } else if km.contains("EAP") || km.contains("IEEE8021X") {
+            Some(Security::Eap)
+        } else if km.contains("PSK") {
+            Some(Security::Wpa2)
+        } else if km.contains("OWE") {
+            Some(Security::Owe)
+        } else if km.contains("WEP") {
+            Some(Security::Wep)
+        } else if km.contains("NONE") {
+            Some(Security::Open)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn shell_escape(s: &str) -> String {
     s.replace("'", "'\\''")
 }
 
-fn run_su_cmd(cmd: &str) -> anyhow::Result<String> {
-    let out = Command::new("su")
+/// Checks `cred` against what `cmd wifi` accepts: a 1–32 byte SSID, and a
+/// passphrase of 8–63 printable ASCII characters. `cmd wifi` quotes every key it
+/// is given, so raw hex keys (a 64-digit PSK, a 10- or 26-digit WEP key) would be
+/// saved as text and are refused.
+pub fn validate_for_add(cred: &WifiCred) -> Result<(), String> {
+    if cred.ssid_hex.is_some() {
+        return Err("SSID isn't valid UTF-8; `cmd wifi` can only add text SSIDs".into());
+    }
+    let ssid = cred.ssid.as_bytes();
+    if ssid.is_empty() || ssid.len() > 32 {
+        return Err(format!("SSID must be 1–32 bytes (got {})", ssid.len()));
+    }
+    if ssid.contains(&0) {
+        return Err("SSID contains a NUL byte".into());
+    }
+    let pass = cred.pass.as_deref().unwrap_or("");
+    let ascii = |p: &str| p.bytes().all(|b| (b' '..=b'~').contains(&b));
+    let hex = |p: &str| p.bytes().all(|b| b.is_ascii_hexdigit());
+    match add_network_type(cred) {
+        "open" | "owe" if !pass.is_empty() => Err("Open and OWE networks take no password".into()),
+        "open" | "owe" => Ok(()),
+        "wep" if [10, 26].contains(&pass.len()) && hex(pass) => {
+            Err("Hex WEP keys can't be added with `cmd wifi`; it only takes text keys".into())
+        }
+        "wep" if [5, 13].contains(&pass.len()) && ascii(pass) => Ok(()),
+        "wep" => Err("WEP key must be 5 or 13 ASCII characters".into()),
+        // SAE passwords have no upper bound; the shell can't carry NUL.
+        "wpa3" if pass.is_empty() || pass.contains('\0') => {
+            Err("WPA3 password must be non-empty, without NUL bytes".into())
+        }
+        "wpa2" if pass.len() == 64 && hex(pass) => Err(
+            "Raw 64-digit hex PSKs can't be added with `cmd wifi`; it only takes passphrases"
+                .into(),
+        ),
+        "wpa2" if !((8..=63).contains(&pass.len()) && ascii(pass)) => {
+            Err("Password must be 8–63 ASCII characters".into())
+        }
+        "eap" => Err("Enterprise (EAP) networks can't be added with `cmd wifi`".into()),
+        _ => Ok(()),
+    }
+}
+
+/// `cmd wifi add-network` security argument for `cred`; without a known
+/// security, a password means WPA2.
+fn add_network_type(cred: &WifiCred) -> &'static str {
+    let has_pass = cred.pass.as_deref().is_some_and(|p| !p.is_empty());
+    match cred.security {
+        Some(s) => s.as_str(),
+        None if has_pass => Security::Wpa2.as_str(),
+        None => Security::Open.as_str(),
+    }
+}
+
+/// Shell that sets `$name` to exactly `value`. The value travels base64-encoded,
+/// so quotes, newlines and `$` never reach the shell parser; the trailing `x`
+/// stops `$(…)` from eating trailing newlines.
+fn shell_assign(name: &str, value: &str) -> String {
+    format!(
+        "{name}=$(printf %s '{}' | base64 -d; echo x); {name}=${{{name}%x}}",
+        B64.encode(value)
+    )
+}
+
+/// `cmd wifi add-network` for `cred`: a line loading `$s` (and `$p`), then the
+/// command that reads them. The SSID is positional and `cmd wifi` takes no
+/// `--`, so a leading dash is safe as long as it stays one quoted word.
+///
+/// `cmd wifi` has no option for picking an Android user, so the network is
+/// always added for the current one.
+fn add_network_shell(cred: &WifiCred) -> (String, String) {
+    let kind = add_network_type(cred);
+    match cred.pass.as_deref().filter(|p| !p.is_empty()) {
+        None => (
+            shell_assign("s", &cred.ssid),
+            format!("cmd wifi add-network \"$s\" {kind}"),
+        ),
+        Some(pass) => (
+            format!(
+                "{}; {}",
+                shell_assign("s", &cred.ssid),
+                shell_assign("p", pass)
+            ),
+            format!("cmd wifi add-network \"$s\" {kind} \"$p\""),
+        ),
+    }
+}
+
+/// An Android user or profile from `pm list users`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AndroidUser {
+    pub id: u32,
+    pub name: String,
+}
+
+/// Parses `pm list users` lines like `UserInfo{10:Work profile:1030} running`.
+pub fn parse_pm_users(out: &str) -> Vec<AndroidUser> {
+    out.lines()
+        .filter_map(|l| {
+            let start = l.find("UserInfo{")? + "UserInfo{".len();
+            let end = start + l[start..].find('}')?;
+            let mut fields = l[start..end].splitn(3, ':');
+            let id = fields.next()?.trim().parse().ok()?;
+            let name = fields.next().unwrap_or_default().to_string();
+            Some(AndroidUser { id, name })
+        })
+        .collect()
+}
+
+/// Users and profiles on the device, via root.
+pub fn list_users() -> Result<Vec<AndroidUser>, SuError> {
+    Ok(parse_pm_users(&run_su_cmd("pm list users")?))
+}
+
+/// Limits for `su` invocations. `command` bounds one command (including the
+/// root manager's prompt); `overall` bounds a multi-command run such as a bulk import.
+#[derive(Clone, Copy, Debug)]
+pub struct SuTimeouts {
+    pub command: Duration,
+    pub overall: Duration,
+}
+
+impl Default for SuTimeouts {
+    fn default() -> Self {
+        SuTimeouts {
+            command: Duration::from_secs(30),
+            overall: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+static SU_TIMEOUTS: Mutex<Option<SuTimeouts>> = Mutex::new(None);
+
+pub fn su_timeouts() -> SuTimeouts {
+    SU_TIMEOUTS.lock().ok().and_then(|t| *t).unwrap_or_default()
+}
+
+pub fn set_su_timeouts(timeouts: SuTimeouts) {
+    if let Ok(mut t) = SU_TIMEOUTS.lock() {
+        *t = Some(timeouts);
+    }
+}
+
+/// Exit status of toybox/coreutils `timeout` when it had to stop the command.
+const TIMEOUT_EXIT: i32 = 124;
+
+/// Runs `cmd` under root-side `timeout` when the device has it. Killing the su
+/// client from our side can't reach root-owned children, so this is what stops
+/// a hung `cmd wifi`.
+fn with_root_timeout(cmd: &str, limit: Duration) -> String {
+    let secs = limit.as_secs().max(1);
+    let quoted = shell_escape(cmd);
+    format!(
+        "if command -v timeout >/dev/null 2>&1; then timeout -k 2 {secs} sh -c '{quoted}'; else sh -c '{quoted}'; fi"
+    )
+}
+
+/// Where a root-side script records its PID, so a second su can stop it: killing
+/// our su client doesn't reach root-owned processes.
+fn root_pid_file() -> String {
+    static NEXT: AtomicU32 = AtomicU32::new(0);
+    format!(
+        "${{TMPDIR:-/data/local/tmp}}/wifi_exporter.{}.{}.pid",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Script prologue writing the shell's PID to `pid_file`, removed on exit.
+fn record_root_pid(pid_file: &str) -> String {
+    format!("echo $$ > \"{pid_file}\"\ntrap 'rm -f \"{pid_file}\"' EXIT\n")
+}
+
+/// Stops the script that wrote `pid_file` and everything it started, children
+/// first so nothing new is spawned meanwhile.
+fn kill_root_script(pid_file: &str) {
+    let cmd = format!(
+        "k() {{ kill -STOP \"$1\" 2>/dev/null; for c in $(pgrep -P \"$1\" 2>/dev/null); do k \"$c\"; done; kill -9 \"$1\" 2>/dev/null; }}\n\
+         [ -s \"{pid_file}\" ] && k \"$(cat \"{pid_file}\")\"; rm -f \"{pid_file}\""
+    );
+    if let Err(e) = run_su_cmd(&cmd) {
+        warn!("Stopping the root-side script failed: {e}");
+    }
+}
+
+/// Starts `su -c cmd` with piped output; a missing binary is [`SuError::SuNotFound`].
+fn spawn_su(cmd: &str) -> Result<Child, SuError> {
+    Command::new("su")
         .arg("-c")
         .arg(cmd)
-        .output()
-        .context("Failed to execute su command")?;
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => SuError::SuNotFound,
+            _ => SuError::CommandFailed {
+                code: None,
+                message: format!("Failed to execute su: {}", e),
+            },
+        })
+}
+
+/// What root managers print when they refuse; `cat: x: Permission denied` from the
+/// command itself doesn't match.
+fn is_root_denial(stderr: &str) -> bool {
+    stderr.lines().any(|l| {
+        let l = l.trim().to_lowercase();
+        l == "permission denied"
+            || (l.starts_with("su:")
+                && (l.contains("denied") || l.contains("not allowed") || l.contains("refused")))
+    })
+}
+
+fn classify_exit(code: Option<i32>, stdout: String, stderr: String, limit: Duration) -> SuError {
+    if code == Some(TIMEOUT_EXIT) {
+        SuError::TimedOut(limit)
+    } else if is_root_denial(&stderr) {
+        SuError::RootDenied
+    } else {
+        SuError::CommandFailed {
+            code,
+            message: if stderr.is_empty() { stdout } else { stderr },
+        }
+    }
+}
 
-    let stdout = decode_utf8(&out.stdout);
-    let stderr = decode_utf8(&out.stderr);
+fn read_all(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut p) = pipe {
+            let _ = p.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+pub fn run_su_cmd(cmd: &str) -> Result<String, SuError> {
+    run_su_raw(cmd).map(|out| decode_utf8(&out))
+}
+
+/// [`run_su_cmd`] without decoding: stdout exactly as the command wrote it.
+fn run_su_raw(cmd: &str) -> Result<Vec<u8>, SuError> {
+    let limit = su_timeouts().command;
+    let mut child = spawn_su(&with_root_timeout(cmd, limit))?;
+    let stdout = read_all(child.stdout.take());
+    let stderr = read_all(child.stderr.take());
+
+    // The root side enforces `limit`; this catches a prompt nobody answers.
+    let deadline = Instant::now() + limit + Duration::from_secs(5);
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(25)),
+            Err(e) => {
+                warn!("Waiting for su failed: {e}");
+                let _ = child.kill();
+                break None;
+            }
+        }
+    };
+    let Some(status) = status else {
+        // Don't join the readers: a root-side child may still hold the pipes.
+        return Err(SuError::TimedOut(limit));
+    };
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = decode_utf8(&stderr.join().unwrap_or_default());
 
-    if out.status.success() {
+    if status.success() {
         Ok(stdout)
     } else {
-        Err(anyhow::anyhow!(
-            "{}",
-            if stderr.is_empty() { &stdout } else { &stderr }
+        Err(classify_exit(
+            status.code(),
+            decode_utf8(&stdout),
+            stderr,
+            limit,
         ))
     }
 }
@@ -45,9 +470,68 @@ fn decode_utf8(data: &[u8]) -> String {
     }
 }
 
-pub fn su_cat(path: &str) -> anyhow::Result<String> {
-    let cmd = format!("cat '{}' 2>/dev/null", path);
-    run_su_cmd(&cmd)
+/// Reads a file as root, byte for byte.
+///
+/// The shell sends `<len> <sha256>` and then the file base64-encoded; both are
+/// checked after decoding. Without `sha256sum` or `base64` it sends `<len> -` and
+/// the raw file instead, which is only checked for length.
+pub fn su_read(path: &str) -> Result<Vec<u8>, ImportError> {
+    let cmd = format!(
+        "f='{}'; n=$(wc -c < \"$f\") || exit 1; \
+         if command -v sha256sum >/dev/null && command -v base64 >/dev/null; then \
+         h=$(sha256sum \"$f\") || exit 1; echo \"$n ${{h%% *}}\"; base64 \"$f\"; \
+         else echo \"$n -\"; cat \"$f\"; fi",
+        shell_escape(path)
+    );
+    let out = run_su_raw(&cmd).map_err(|e| su_read_error(path, e))?;
+    let corrupt = |why: &str| ImportError::Io {
+        path: path.to_string(),
+        message: format!("transfer integrity check failed: {}", why),
+    };
+
+    let split = out.iter().position(|&b| b == b'\n').unwrap_or(out.len());
+    let header = String::from_utf8_lossy(&out[..split]);
+    let body = out.get(split + 1..).unwrap_or_default();
+    let mut fields = header.split_whitespace();
+    let len: usize = fields
+        .next()
+        .and_then(|n| n.parse().ok())
+        .ok_or_else(|| corrupt("missing length"))?;
+    let digest = fields.next().ok_or_else(|| corrupt("missing checksum"))?;
+    let bytes = if digest == "-" {
+        body.to_vec()
+    } else {
+        let b64: Vec<u8> = body
+            .iter()
+            .copied()
+            .filter(|b| !b.is_ascii_whitespace())
+            .collect();
+        B64.decode(b64).map_err(|_| corrupt("bad base64"))?
+    };
+    if bytes.len() != len {
+        return Err(corrupt(&format!("got {} of {} bytes", bytes.len(), len)));
+    }
+    if digest != "-" && !hex_encode(&Sha256::digest(&bytes)).eq_ignore_ascii_case(digest) {
+        return Err(corrupt("sha256 mismatch"));
+    }
+    Ok(bytes)
+}
+
+fn su_read_error(path: &str, e: SuError) -> ImportError {
+    let SuError::CommandFailed { message, .. } = e else {
+        return ImportError::Root(e);
+    };
+    let lower = message.to_lowercase();
+    if lower.contains("no such file") {
+        ImportError::NotFound(path.to_string())
+    } else if lower.contains("permission denied") {
+        ImportError::PermissionDenied(path.to_string())
+    } else {
+        ImportError::Io {
+            path: path.to_string(),
+            message,
+        }
+    }
 }
 
 static API_LEVEL: OnceLock<i32> = OnceLock::new();
@@ -68,7 +552,22 @@ pub fn get_api_level() -> i32 {
     })
 }
 
-/// Adds a single network to system (Android 11+ only)
+static DEVICE_MODEL: OnceLock<Option<String>> = OnceLock::new();
+
+pub fn get_device_model() -> Option<String> {
+    DEVICE_MODEL
+        .get_or_init(|| {
+            Command::new("getprop")
+                .arg("ro.product.model")
+                .output()
+                .ok()
+                .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+                .filter(|m| !m.is_empty())
+        })
+        .clone()
+}
+
+/// Adds a single network to system (Android 11+ only).
 pub fn su_add_network(cred: &WifiCred) -> anyhow::Result<String> {
     let api = get_api_level();
     if api < 30 {
@@ -78,204 +577,2733 @@ pub fn su_add_network(cred: &WifiCred) -> anyhow::Result<String> {
         ));
     }
 
-    let ssid = shell_escape(&cred.ssid);
-    let pass = cred.pass.as_deref().unwrap_or("");
-
-    let cmd = if pass.is_empty() {
-        format!("cmd wifi add-network '{}' open", ssid)
-    } else {
-        let esc_pass = shell_escape(pass);
-        format!("cmd wifi add-network '{}' wpa2 '{}'", ssid, esc_pass)
-    };
+    validate_for_add(cred).map_err(|e| anyhow::anyhow!(e))?;
+    let (setup, add) = add_network_shell(cred);
+    Ok(run_su_cmd(&format!("{}\n{}", setup, add))?)
+}
 
-    run_su_cmd(&cmd)
+/// One network's outcome during [`su_import_all`].
+pub struct AddResult {
+    pub index: usize,
+    pub ssid: String,
+    /// `cmd wifi` output when it failed.
+    pub error: Option<String>,
 }
 
 /// Bulk import all networks to system (Android 11+ only) via a single `su` invocation.
-pub fn su_import_all(creds: &[WifiCred]) -> (usize, usize, Vec<(String, String)>) {
+///
+/// su's stdout is read as it arrives, so `on_result` fires once per network while
+/// the import runs. `cancelled` is polled throughout; returning true, or hitting
+/// the overall timeout, kills the root-side script. Networks it hadn't reported
+/// by then count as [`ImportSummary::unfinished`], not failed: the one running
+/// may still have been added.
+pub fn su_import_all(
+    creds: &[WifiCred],
+    mut on_result: impl FnMut(&AddResult),
+    cancelled: impl Fn() -> bool,
+) -> Result<ImportSummary, SuError> {
+    let mut summary = ImportSummary::default();
     if creds.is_empty() {
-        return (0, 0, vec![]);
+        return Ok(summary);
     }
+    let limits = su_timeouts();
 
-    let mut script = String::new();
+    let timeout_msg = format!("timed out after {}s", limits.command.as_secs());
+    let pid_file = root_pid_file();
+    let mut script = record_root_pid(&pid_file);
+    // `t` applies the per-network limit root-side where `timeout` exists.
+    script.push_str(&format!(
+        "t() {{ if command -v timeout >/dev/null 2>&1; then timeout -k 2 {} \"$@\"; else \"$@\"; fi; }}\n",
+        limits.command.as_secs().max(1)
+    ));
+    let mut seen = vec![false; creds.len()];
     for (i, cred) in creds.iter().enumerate() {
-        let ssid = shell_escape(&cred.ssid);
-        let pass = cred.pass.as_deref().unwrap_or("");
-        if pass.is_empty() {
-            script.push_str(&format!(
-                "{{ cmd wifi add-network '{}' open 2>/dev/null; }} && echo 'OK:{}' || echo 'FAIL:{}'\n",
-                ssid, i, i
-            ));
+        // Invalid entries are reported up front and never reach the shell.
+        if let Err(e) = validate_for_add(cred) {
+            seen[i] = true;
+            summary.record(&cred.ssid, Some(&e));
+            on_result(&AddResult {
+                index: i,
+                ssid: cred.ssid.clone(),
+                error: Some(e),
+            });
+            continue;
+        }
+        let (setup, add) = add_network_shell(cred);
+        script.push_str(&setup);
+        script.push('\n');
+        script.push_str(&format!(
+            "if out=$(t {add} 2>&1); then echo 'OK:{i}'; else [ $? -eq {TIMEOUT_EXIT} ] && out='{timeout_msg}'; echo \"FAIL:{i}:$(printf '%s' \"$out\" | tr '\\n' ' ')\"; fi\n",
+        ));
+    }
+
+    let rejected = summary.failed;
+    if rejected == creds.len() {
+        return Ok(summary);
+    }
+
+    let mut child = spawn_su(&script)?;
+    let stderr = read_all(child.stderr.take());
+    let Some(stdout) = child.stdout.take() else {
+        let _ = child.kill();
+        return Err(SuError::CommandFailed {
+            code: None,
+            message: "No output from su".into(),
+        });
+    };
+    // Root-side children can hold the pipe open after su is killed, so lines
+    // come through a channel and the wait below never blocks on EOF.
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut handle_line = |line: &str| {
+        let (idx, error) = if let Some(idx) = line.strip_prefix("OK:") {
+            (idx.trim().parse::<usize>().ok(), None)
+        } else if let Some(rest) = line.strip_prefix("FAIL:") {
+            let (idx, msg) = rest.split_once(':').unwrap_or((rest, ""));
+            let msg = msg.trim();
+            let msg = if msg.is_empty() {
+                "cmd wifi failed"
+            } else {
+                msg
+            };
+            (idx.parse::<usize>().ok(), Some(msg.to_string()))
         } else {
-            script.push_str(&format!(
-                "{{ cmd wifi add-network '{}' wpa2 '{}' 2>/dev/null; }} && echo 'OK:{}' || echo 'FAIL:{}'\n",
-                ssid, shell_escape(pass), i, i
-            ));
+            return;
+        };
+        let Some(idx) = idx.filter(|&i| i < creds.len() && !seen[i]) else {
+            return;
+        };
+        seen[idx] = true;
+        summary.record(&creds[idx].ssid, error.as_deref());
+        on_result(&AddResult {
+            index: idx,
+            ssid: creds[idx].ssid.clone(),
+            error,
+        });
+    };
+
+    let deadline = Instant::now() + limits.overall;
+    let mut timed_out = false;
+    let mut stopped = false;
+    loop {
+        if cancelled() || Instant::now() >= deadline {
+            timed_out = !cancelled();
+            stopped = true;
+            kill_root_script(&pid_file);
+            let _ = child.kill();
+            break;
+        }
+        match rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(line) => handle_line(&line),
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
         }
     }
+    if stopped {
+        // Results printed before the kill are still in the pipe; with the tree
+        // gone it closes, unless the kill itself failed.
+        let drain_until = Instant::now() + Duration::from_secs(2);
+        while let Some(left) = drain_until.checked_duration_since(Instant::now()) {
+            match rx.recv_timeout(left) {
+                Ok(line) => handle_line(&line),
+                Err(_) => break,
+            }
+        }
+    }
+    let status = child.wait().ok();
+    // Only read stderr when su exited by itself; otherwise it may never close.
+    let stderr = if stopped {
+        String::new()
+    } else {
+        decode_utf8(&stderr.join().unwrap_or_default())
+    };
+    summary.unfinished = seen.iter().filter(|s| !**s).count();
 
-    match run_su_cmd(&script) {
-        Ok(output) => {
-            let mut success = 0;
-            let mut failed = 0;
-            let mut errors = Vec::new();
-            for line in output.lines() {
-                if let Some(idx_str) = line.strip_prefix("OK:") {
-                    if idx_str.parse::<usize>().is_ok() {
-                        success += 1;
-                    }
-                } else if let Some(idx_str) = line.strip_prefix("FAIL:") {
-                    if let Ok(idx) = idx_str.parse::<usize>() {
-                        failed += 1;
-                        if let Some(cred) = creds.get(idx) {
-                            errors.push((cred.ssid.clone(), "cmd wifi failed".into()));
-                        }
-                    }
+    if summary.success == 0 && summary.failed == rejected && !cancelled() {
+        return Err(if timed_out {
+            SuError::TimedOut(limits.overall)
+        } else {
+            match status {
+                Some(s) if !s.success() => {
+                    classify_exit(s.code(), String::new(), stderr, limits.command)
                 }
+                _ => SuError::CommandFailed {
+                    code: status.and_then(|s| s.code()),
+                    message: "No output markers from import".into(),
+                },
+            }
+        });
+    }
+    Ok(summary)
+}
+
+/// Totals of a [`su_import_all`] run.
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub success: usize,
+    pub failed: usize,
+    /// (SSID, reason) per failed network.
+    pub errors: Vec<(String, String)>,
+    /// Networks without a result when the run was stopped (overall timeout or
+    /// cancel). The one being added at that moment may or may not be saved.
+    pub unfinished: usize,
+}
+
+impl ImportSummary {
+    fn record(&mut self, ssid: &str, error: Option<&str>) {
+        match error {
+            None => self.success += 1,
+            Some(e) => {
+                self.failed += 1;
+                self.errors.push((ssid.to_string(), e.to_string()));
             }
-            if success == 0 && failed == 0 {
-                failed = creds.len();
-                for cred in creds {
-                    errors.push((cred.ssid.clone(), "No output markers from import".into()));
+        }
+    }
+}
+
+/// Version of the JSON envelope written by [`build_json`]; see `schema/`.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Versioned JSON export. Older releases wrote a bare array or `{"networks": [...]}`,
+/// both of which are still read.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportEnvelope {
+    pub format_version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_level: Option<i32>,
+    /// Config file the networks were read from on the source device.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_path: Option<String>,
+    /// Unix seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_count: Option<usize>,
+    /// `sha256:<hex>` over the compact JSON serialization of `networks`, keys in
+    /// file order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    pub networks: Vec<WifiCred>,
+}
+
+/// Hashes the `networks` value as it appears in the file, so fields this version
+/// doesn't know about are still covered.
+fn networks_checksum(networks: &serde_json::Value) -> String {
+    let canonical = serde_json::to_vec(networks).unwrap_or_default();
+    format!(
+        "sha256:{}",
+        hex_encode(&Sha256::digest(&canonical)).to_lowercase()
+    )
+}
+
+fn check_format_version(
+    obj: &serde_json::Map<String, serde_json::Value>,
+) -> Result<(), ImportError> {
+    let Some(v) = obj.get("format_version") else {
+        return Ok(());
+    };
+    let version = v.as_u64().ok_or_else(|| ImportError::SchemaMismatch {
+        path: "format_version".into(),
+        line: 0,
+        column: 0,
+        message: "expected a positive integer".into(),
+    })?;
+    if version > FORMAT_VERSION as u64 {
+        return Err(ImportError::UnsupportedVersion {
+            found: version,
+            supported: FORMAT_VERSION,
+        });
+    }
+    Ok(())
+}
+
+/// A network entry skipped by a lenient import.
+#[derive(Clone, Debug)]
+pub struct RejectedEntry {
+    pub index: usize,
+    pub reason: String,
+    /// Compact JSON of the entry, shortened for display.
+    pub snippet: String,
+}
+
+const SNIPPET_CHARS: usize = 120;
+
+/// Like [`parse_imported_json`], but decodes every network on its own and keeps the
+/// good ones. Syntax errors and unsupported versions still fail the whole file; the
+/// envelope checksum is not checked since the point is to salvage edited files.
+pub fn parse_imported_json_lenient(json: &str) -> Result<ParsedImport, ImportError> {
+    let value: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| ImportError::malformed_json("JSON", &e))?;
+    let entries = match value {
+        serde_json::Value::Array(a) => a,
+        serde_json::Value::Object(mut obj) => {
+            check_format_version(&obj)?;
+            match obj.remove("networks") {
+                Some(serde_json::Value::Array(a)) => a,
+                _ => {
+                    return Err(ImportError::SchemaMismatch {
+                        path: "networks".into(),
+                        line: 0,
+                        column: 0,
+                        message: "expected an array of networks".into(),
+                    });
                 }
             }
-            (success, failed, errors)
         }
-        Err(e) => {
-            let errors: Vec<_> = creds
-                .iter()
-                .map(|c| (c.ssid.clone(), e.to_string()))
-                .collect();
-            (0, creds.len(), errors)
+        _ => {
+            return Err(ImportError::SchemaMismatch {
+                path: String::new(),
+                line: 1,
+                column: 1,
+                message: "expected a network list or export envelope".into(),
+            });
+        }
+    };
+
+    let mut parsed = ParsedImport::default();
+    for (index, entry) in entries.into_iter().enumerate() {
+        let mut snippet = entry.to_string();
+        if snippet.chars().count() > SNIPPET_CHARS {
+            snippet = snippet.chars().take(SNIPPET_CHARS).collect::<String>() + "…";
         }
+        let reason = match serde_path_to_error::deserialize::<_, WifiCred>(entry) {
+            Ok(c) if c.ssid.trim().is_empty() => "ssid: empty".to_string(),
+            Ok(c) => {
+                parsed.networks.push(c);
+                continue;
+            }
+            Err(e) => {
+                let path = e.path().to_string();
+                if path == "." {
+                    e.into_inner().to_string()
+                } else {
+                    format!("{}: {}", path, e.into_inner())
+                }
+            }
+        };
+        parsed.rejected.push(RejectedEntry {
+            index,
+            reason,
+            snippet,
+        });
+    }
+    if parsed.networks.is_empty() && parsed.rejected.is_empty() {
+        return Err(ImportError::NoNetworks("JSON"));
     }
+    Ok(parsed)
 }
 
-pub fn parse_imported_json(json: &str) -> anyhow::Result<Vec<WifiCred>> {
-    if let Ok(creds) = serde_json::from_str::<Vec<WifiCred>>(json) {
-        return Ok(creds);
-    }
+/// Deserializes `json`, reporting the failing field path and position.
+fn from_json_str<T: DeserializeOwned>(json: &str) -> Result<T, ImportError> {
+    let de = &mut serde_json::Deserializer::from_str(json);
+    serde_path_to_error::deserialize(de).map_err(ImportError::schema)
+}
 
-    #[derive(Deserialize)]
-    struct Wrapped {
-        networks: Vec<WifiCred>,
+pub fn parse_imported_json(json: &str) -> Result<Vec<WifiCred>, ImportError> {
+    let value: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| ImportError::malformed_json("JSON", &e))?;
+
+    match &value {
+        serde_json::Value::Array(_) => from_json_str::<Vec<WifiCred>>(json),
+        serde_json::Value::Object(obj) if obj.contains_key("format_version") => {
+            check_format_version(obj)?;
+            let env: ExportEnvelope = from_json_str(json)?;
+            if let Some(sum) = &env.checksum
+                && !sum.eq_ignore_ascii_case(&networks_checksum(&obj["networks"]))
+            {
+                return Err(ImportError::ChecksumMismatch);
+            }
+            if let Some(n) = env.network_count
+                && n != env.networks.len()
+            {
+                warn!(
+                    "Envelope says {} networks but contains {}",
+                    n,
+                    env.networks.len()
+                );
+            }
+            info!(
+                "Importing v{} export from {} (API {})",
+                env.format_version,
+                env.device_model.as_deref().unwrap_or("unknown device"),
+                env.api_level.map_or("?".to_string(), |a| a.to_string())
+            );
+            Ok(env.networks)
+        }
+        serde_json::Value::Object(obj) if obj.contains_key("networks") => {
+            #[derive(Deserialize)]
+            struct Wrapped {
+                networks: Vec<WifiCred>,
+            }
+            from_json_str::<Wrapped>(json).map(|w| w.networks)
+        }
+        _ => Err(ImportError::SchemaMismatch {
+            path: String::new(),
+            line: 1,
+            column: 1,
+            message: "expected a network list or export envelope".into(),
+        }),
     }
-    if let Ok(wrapped) = serde_json::from_str::<Wrapped>(json) {
-        return Ok(wrapped.networks);
+}
+
+fn require_networks(v: Vec<WifiCred>, what: &'static str) -> Result<Vec<WifiCred>, ImportError> {
+    if v.is_empty() {
+        Err(ImportError::NoNetworks(what))
+    } else {
+        Ok(v)
     }
+}
 
-    Err(anyhow::anyhow!("Invalid JSON format"))
+/// How to read an import file. Only the key matching the file is used.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ImportOptions<'a> {
+    pub passphrase: Option<&'a str>,
+    /// Contents of an age identity file (`AGE-SECRET-KEY-1…` lines).
+    pub age_identity: Option<&'a str>,
+    /// Skip bad JSON entries instead of rejecting the file.
+    pub lenient: bool,
+    /// Columns picked by the user for a CSV file, instead of the guessed ones.
+    pub csv_mapping: Option<&'a CsvMapping>,
 }
 
-pub fn build_json(creds: &[WifiCred]) -> String {
-    serde_json::to_string_pretty(creds).unwrap_or_else(|_| "[]".to_string())
+#[derive(Debug, Default)]
+pub struct ParsedImport {
+    pub networks: Vec<WifiCred>,
+    /// Entries dropped by a lenient JSON import.
+    pub rejected: Vec<RejectedEntry>,
 }
 
-pub fn parse_wifi_configstore_xml(xml: &str) -> Vec<WifiCred> {
-    let mut out = Vec::new();
-    for blk in xml.split("<Network>").skip(1) {
-        let end = blk.find("</Network>").unwrap_or(blk.len());
-        let chunk = &blk[..end];
-        let ssid = find_string(chunk, "SSID")
-            .or_else(|| find_string(chunk, "ConfigKey"))
-            .map(strip_quotes);
-        let psk = find_string(chunk, "PreSharedKey").map(strip_quotes);
-        if let Some(s) = ssid {
-            out.push(WifiCred { ssid: s, pass: psk });
-        }
+/// Parses an import file's raw bytes, picking the reader from [`IMPORT_READERS`] by content.
+/// Encrypted exports need a passphrase or age identity in `opts`; check
+/// [`crypto::is_encrypted_export`] / [`crypto::is_age_export`] first to obtain them.
+pub fn parse_import_bytes(
+    bytes: &[u8],
+    opts: ImportOptions<'_>,
+) -> Result<ParsedImport, ImportError> {
+    let inner = ImportOptions {
+        lenient: opts.lenient,
+        ..Default::default()
+    };
+    let head = sniff(&bytes[..bytes.len().min(256)]);
+    if crypto::is_encrypted_export(&head) {
+        let pass = opts
+            .passphrase
+            .ok_or_else(|| ImportError::DecryptionFailed("passphrase required".into()))?;
+        let plain = crypto::decrypt_export(&sniff(bytes), pass).map_err(ImportError::decryption)?;
+        return parse_import_bytes(&plain, inner);
     }
-    out
+    if crypto::is_age_export(&head) {
+        let identity = opts
+            .age_identity
+            .ok_or_else(|| ImportError::DecryptionFailed("age identity required".into()))?;
+        let plain =
+            crypto::decrypt_with_identity(bytes, identity).map_err(ImportError::decryption)?;
+        return parse_import_bytes(&plain, inner);
+    }
+
+    let (reader, score) = detect_reader(bytes)
+        .ok_or_else(|| ImportError::Unsupported("Unrecognised file format".into()))?;
+    info!("Import detected as {} (confidence {})", reader.name, score);
+    if opts.lenient && reader.id == "json" {
+        return parse_imported_json_lenient(utf8(bytes)?);
+    }
+    if let Some(mapping) = opts.csv_mapping.filter(|_| reader.id == "csv") {
+        return Ok(ParsedImport {
+            networks: require_networks(parse_csv(utf8(bytes)?, mapping), "CSV")?,
+            rejected: Vec::new(),
+        });
+    }
+    Ok(ParsedImport {
+        networks: (reader.parse)(bytes)?,
+        rejected: Vec::new(),
+    })
 }
 
-fn find_string(hay: &str, name: &str) -> Option<String> {
-    let needle = format!("<string name=\"{}\">", name);
-    let i = hay.find(&needle)?;
-    let start = i + needle.len();
-    let rest = &hay[start..];
-    let end = rest.find("</string>")?;
-    Some(html_unescape(rest[..end].trim()))
+/// Wraps `creds` in a versioned [`ExportEnvelope`] stamped with this device's details.
+pub fn build_json(creds: &[WifiCred], stamp: u64, source_path: Option<&str>) -> String {
+    let env = ExportEnvelope {
+        format_version: FORMAT_VERSION,
+        app_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        device_model: get_device_model(),
+        api_level: Some(get_api_level()),
+        source_path: source_path.map(str::to_string),
+        exported_at: Some(stamp),
+        network_count: Some(creds.len()),
+        checksum: serde_json::to_value(creds)
+            .ok()
+            .map(|v| networks_checksum(&v)),
+        networks: creds.to_vec(),
+    };
+    serde_json::to_string_pretty(&env).unwrap_or_else(|_| "[]".to_string())
 }
 
-fn html_unescape(s: &str) -> String {
-    s.replace("&quot;", "\"")
-        .replace("&amp;", "&")
-        .replace("&lt;", "<")
-        .replace("&gt;", ">")
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Encrypted,
+    Age,
+    WindowsWlan,
+    AppleProfile,
+    OpenWrt,
+    Csv,
+    Bitwarden,
+    KeePass,
+    OnePassword,
 }
 
-fn strip_quotes<S: AsRef<str>>(s: S) -> String {
-    let t = s.as_ref().trim();
-    if t.starts_with('"') && t.ends_with('"') && t.len() >= 2 {
-        t[1..t.len() - 1].to_string()
-    } else {
-        t.to_string()
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 10] = [
+        ExportFormat::Json,
+        ExportFormat::Encrypted,
+        ExportFormat::Age,
+        ExportFormat::Csv,
+        ExportFormat::WindowsWlan,
+        ExportFormat::AppleProfile,
+        ExportFormat::OpenWrt,
+        ExportFormat::Bitwarden,
+        ExportFormat::KeePass,
+        ExportFormat::OnePassword,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "JSON",
+            ExportFormat::Encrypted => "Encrypted",
+            ExportFormat::Age => "age",
+            ExportFormat::WindowsWlan => "Windows",
+            ExportFormat::AppleProfile => "Apple",
+            ExportFormat::OpenWrt => "OpenWrt",
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Bitwarden => "Bitwarden",
+            ExportFormat::KeePass => "KeePass",
+            ExportFormat::OnePassword => "1Password",
+        }
+    }
+
+    pub fn mime(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Encrypted | ExportFormat::Age => crypto::MIME,
+            ExportFormat::WindowsWlan => "application/xml",
+            ExportFormat::AppleProfile => "application/x-apple-aspen-config",
+            ExportFormat::OpenWrt => "text/plain",
+            ExportFormat::Csv | ExportFormat::OnePassword => "text/csv",
+            ExportFormat::Bitwarden => "application/json",
+            ExportFormat::KeePass => "application/xml",
+        }
     }
 }
 
-pub fn parse_wpa_supplicant(conf: &str) -> Vec<WifiCred> {
-    let mut out = Vec::new();
-    let mut ssid: Option<String> = None;
-    let mut psk: Option<String> = None;
-    let mut in_blk = false;
+pub struct ExportFile {
+    pub name: String,
+    pub mime: &'static str,
+    pub contents: String,
+}
 
-    for line in conf.lines() {
-        let l = line.trim();
-        if l.starts_with("network={") {
-            in_blk = true;
-            ssid = None;
-            psk = None;
-            continue;
+#[derive(Clone, Debug, Default)]
+pub struct ExportOptions {
+    pub csv: CsvOptions,
+    pub uci: UciOptions,
+    /// Required for [`ExportFormat::Encrypted`].
+    pub passphrase: Option<zeroize::Zeroizing<String>>,
+    /// `age1…` X25519 recipients for [`ExportFormat::Age`].
+    pub age_recipients: Vec<String>,
+    /// Recorded in the JSON envelope.
+    pub source_path: Option<String>,
+}
+
+/// Builds the file(s) for `format`; Windows profiles are one file per network.
+pub fn build_export(
+    format: ExportFormat,
+    creds: &[WifiCred],
+    stamp: u64,
+    opts: &ExportOptions,
+) -> anyhow::Result<Vec<ExportFile>> {
+    Ok(match format {
+        ExportFormat::Json => vec![ExportFile {
+            name: format!("wifi_passwords_{}.json", stamp),
+            mime: format.mime(),
+            contents: build_json(creds, stamp, opts.source_path.as_deref()),
+        }],
+        ExportFormat::Encrypted => {
+            let pass = opts
+                .passphrase
+                .as_deref()
+                .context("Passphrase required for encrypted export")?;
+            vec![ExportFile {
+                name: format!("wifi_passwords_{}.{}", stamp, crypto::EXTENSION),
+                mime: format.mime(),
+                contents: crypto::encrypt_export(
+                    build_json(creds, stamp, opts.source_path.as_deref()).as_bytes(),
+                    pass,
+                )?,
+            }]
         }
-        if in_blk && l.starts_with('}') {
-            if let Some(s) = ssid.take() {
-                out.push(WifiCred {
-                    ssid: s,
-                    pass: psk.take(),
-                });
+        ExportFormat::Age => vec![ExportFile {
+            name: format!("wifi_passwords_{}.json.{}", stamp, crypto::AGE_EXTENSION),
+            mime: format.mime(),
+            contents: crypto::encrypt_to_recipients(
+                build_json(creds, stamp, opts.source_path.as_deref()).as_bytes(),
+                &opts.age_recipients,
+            )?,
+        }],
+        ExportFormat::WindowsWlan => {
+            let mut names = HashSet::new();
+            let files: Vec<ExportFile> = creds
+                .iter()
+                .filter_map(|c| {
+                    Some(ExportFile {
+                        contents: build_wlan_profile(c)?,
+                        name: unique_name(
+                            &mut names,
+                            &format!("Wi-Fi-{}", file_safe(&c.ssid)),
+                            "xml",
+                        ),
+                        mime: format.mime(),
+                    })
+                })
+                .collect();
+            if files.is_empty() {
+                anyhow::bail!(
+                    "No networks to export: 802.1X (EAP) networks can't be exported for Windows"
+                );
             }
-            in_blk = false;
-            continue;
-        }
-        if !in_blk {
-            continue;
+            files
         }
-        if let Some(eq) = l.find('=') {
-            let k = &l[..eq];
-            let v = &l[eq + 1..];
-            match k {
-                "ssid" => ssid = Some(strip_quotes(v)),
-                "psk" => psk = Some(strip_quotes(v)),
-                _ => {}
+        ExportFormat::AppleProfile => vec![ExportFile {
+            name: format!("wifi_networks_{}.mobileconfig", stamp),
+            mime: format.mime(),
+            contents: build_mobileconfig(creds),
+        }],
+        ExportFormat::OpenWrt => {
+            if creds.iter().all(|c| c.security() == Security::Eap) {
+                anyhow::bail!(
+                    "No networks to export: 802.1X (EAP) networks can't be exported for OpenWrt"
+                );
             }
+            vec![ExportFile {
+                name: format!("wireless_{}.uci", stamp),
+                mime: format.mime(),
+                contents: build_uci_wireless(creds, &opts.uci),
+            }]
         }
-    }
-    out
+        ExportFormat::Csv => vec![ExportFile {
+            name: format!("wifi_passwords_{}.csv", stamp),
+            mime: format.mime(),
+            contents: build_csv(creds, &opts.csv),
+        }],
+        ExportFormat::Bitwarden => vec![ExportFile {
+            name: format!("bitwarden_wifi_{}.json", stamp),
+            mime: format.mime(),
+            contents: build_bitwarden_json(creds),
+        }],
+        ExportFormat::KeePass => vec![ExportFile {
+            name: format!("keepass_wifi_{}.xml", stamp),
+            mime: format.mime(),
+            contents: build_keepass_xml(creds),
+        }],
+        ExportFormat::OnePassword => vec![ExportFile {
+            name: format!("1password_wifi_{}.csv", stamp),
+            mime: format.mime(),
+            contents: build_1password_csv(creds),
+        }],
+    })
 }
 
-pub fn try_read_with_su() -> anyhow::Result<Vec<WifiCred>> {
-    let candidates = [
-        "/data/misc/apexdata/com.android.wifi/WifiConfigStore.xml",
-        "/data/misc/wifi/WifiConfigStore.xml",
-        "/data/misc/wifi/WifiConfigStore.conf",
-        "/data/misc/wifi/wpa_supplicant.conf",
-    ];
-    for p in candidates {
-        if let Ok(text) = su_cat(p) {
-            info!("Read config via su: {}", p);
-            if p.ends_with(".xml") || text.contains("<Network>") {
-                let v = parse_wifi_configstore_xml(&text);
-                if !v.is_empty() {
-                    return Ok(v);
-                }
-            } else if text.contains("network={") {
-                let v = parse_wpa_supplicant(&text);
-                if !v.is_empty() {
-                    return Ok(v);
+fn file_safe(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// `stem.ext`, or `stem-2.ext`, `stem-3.ext`… if an earlier file took that name.
+/// Compared case-insensitively, like the file systems the files end up on.
+fn unique_name(taken: &mut HashSet<String>, stem: &str, ext: &str) -> String {
+    let mut name = format!("{}.{}", stem, ext);
+    let mut n = 1;
+    while !taken.insert(name.to_lowercase()) {
+        n += 1;
+        name = format!("{}-{}.{}", stem, n, ext);
+    }
+    name
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+fn hex_decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim();
+    if !s.len().is_multiple_of(2) || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Windows `WLANProfile` XML, as consumed by `netsh wlan add profile filename=`.
+///
+/// `None` for EAP networks: Windows needs the EAP method and its settings in a
+/// `<OneX>` block, and none of that is exported.
+pub fn build_wlan_profile(cred: &WifiCred) -> Option<String> {
+    let name = xml_escape(&cred.ssid);
+    let pass = cred.pass.as_deref().unwrap_or("");
+    let (auth, enc) = match cred.security() {
+        Security::Open => ("open", "none"),
+        Security::Owe => ("OWE", "AES"),
+        Security::Wep => ("open", "WEP"),
+        Security::Wpa2 => ("WPA2PSK", "AES"),
+        Security::Wpa3 => ("WPA3SAE", "AES"),
+        Security::Eap => return None,
+    };
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\"?>\n");
+    xml.push_str("<WLANProfile xmlns=\"http://www.microsoft.com/networking/WLAN/profile/v1\">\n");
+    xml.push_str(&format!("\t<name>{}</name>\n", name));
+    xml.push_str("\t<SSIDConfig>\n\t\t<SSID>\n");
+    xml.push_str(&format!(
+        "\t\t\t<hex>{}</hex>\n",
+        hex_encode(&cred.ssid_bytes())
+    ));
+    xml.push_str(&format!("\t\t\t<name>{}</name>\n", name));
+    xml.push_str("\t\t</SSID>\n");
+    if cred.hidden {
+        xml.push_str("\t\t<nonBroadcast>true</nonBroadcast>\n");
+    }
+    xml.push_str("\t</SSIDConfig>\n");
+    xml.push_str("\t<connectionType>ESS</connectionType>\n");
+    xml.push_str("\t<connectionMode>auto</connectionMode>\n");
+    xml.push_str("\t<MSM>\n\t\t<security>\n\t\t\t<authEncryption>\n");
+    xml.push_str(&format!(
+        "\t\t\t\t<authentication>{}</authentication>\n",
+        auth
+    ));
+    xml.push_str(&format!("\t\t\t\t<encryption>{}</encryption>\n", enc));
+    xml.push_str("\t\t\t\t<useOneX>false</useOneX>\n");
+    xml.push_str("\t\t\t</authEncryption>\n");
+    if !pass.is_empty() {
+        // WEP keys and raw 64-hex PSKs are network keys; everything else is a passphrase.
+        let key_type = if cred.security() == Security::Wep
+            || (pass.len() == 64 && hex_decode(pass).is_some())
+        {
+            "networkKey"
+        } else {
+            "passPhrase"
+        };
+        xml.push_str("\t\t\t<sharedKey>\n");
+        xml.push_str(&format!("\t\t\t\t<keyType>{}</keyType>\n", key_type));
+        xml.push_str("\t\t\t\t<protected>false</protected>\n");
+        xml.push_str(&format!(
+            "\t\t\t\t<keyMaterial>{}</keyMaterial>\n",
+            xml_escape(pass)
+        ));
+        xml.push_str("\t\t\t</sharedKey>\n");
+    }
+    xml.push_str("\t\t</security>\n\t</MSM>\n");
+    xml.push_str("</WLANProfile>\n");
+    Some(xml)
+}
+
+/// Parses one or more `WLANProfile` documents, e.g. from `netsh wlan export profile key=clear`.
+pub fn parse_wlan_profiles(xml: &str) -> Vec<WifiCred> {
+    let mut out = Vec::new();
+    for blk in xml.split("<WLANProfile").skip(1) {
+        let end = blk.find("</WLANProfile>").unwrap_or(blk.len());
+        let chunk = &blk[..end];
+
+        // `<hex>` is exact; `<name>` may have been re-encoded or be missing.
+        let ssid_blk = find_element(chunk, "SSID");
+        let ssid = ssid_blk
+            .as_deref()
+            .and_then(|b| find_element(b, "hex"))
+            .filter(|h| hex_decode(h).is_some_and(|b| !b.is_empty()))
+            .map(|h| decode_ssid(&h))
+            .or_else(|| {
+                ssid_blk
+                    .as_deref()
+                    .and_then(|b| find_element(b, "name"))
+                    .or_else(|| find_element(chunk, "name"))
+                    .map(|n| (n, None))
+            });
+        let Some((ssid, ssid_hex)) = ssid else {
+            continue;
+        };
+
+        let auth = find_element(chunk, "authentication").unwrap_or_default();
+        let enc = find_element(chunk, "encryption").unwrap_or_default();
+        let security = match auth.to_ascii_uppercase().as_str() {
+            "WPA3SAE" | "WPA3" => Security::Wpa3,
+            "WPA2PSK" | "WPAPSK" => Security::Wpa2,
+            "WPA2" | "WPA" | "WPA3ENT" | "WPA3ENT192" => Security::Eap,
+            "OWE" => Security::Owe,
+            _ if enc.eq_ignore_ascii_case("WEP") => Security::Wep,
+            _ => Security::Open,
+        };
+        let pass = find_element(chunk, "keyMaterial").filter(|k| !k.is_empty());
+
+        out.push(WifiCred {
+            ssid,
+            ssid_hex,
+            pass,
+            security: Some(security),
+            hidden: find_element(chunk, "nonBroadcast").as_deref() == Some("true"),
+            ..Default::default()
+        });
+    }
+    out
+}
+
+const PROFILE_ID_PREFIX: &str = "dev.mlm.wifi_exporter";
+
+/// Namespace for name-based UUIDs, so re-exports replace rather than duplicate.
+const STABLE_UUID_NS: uuid::Uuid = uuid::Uuid::from_u128(0x5b0e_7c1a_2f4d_4e8b_9a63_1d2c_7f80_a6e4);
+
+fn stable_uuid(seed: &str) -> uuid::Uuid {
+    uuid::Uuid::new_v5(&STABLE_UUID_NS, seed.as_bytes())
+}
+
+fn payload_uuid(seed: &str) -> String {
+    stable_uuid(seed).hyphenated().to_string().to_uppercase()
+}
+
+/// UUID seed for one network in an export `kind`, built from
+/// [`WifiCred::network_key`] so same-SSID networks with other security don't
+/// collide.
+fn network_seed(kind: &str, c: &WifiCred) -> String {
+    format!(
+        "{}:{}:{}",
+        kind,
+        hex_encode(&c.ssid_bytes()),
+        c.security().as_str()
+    )
+}
+
+/// Unsigned Apple configuration profile with one `com.apple.wifi.managed` payload per network.
+pub fn build_mobileconfig(creds: &[WifiCred]) -> String {
+    let mut payloads = String::new();
+    for c in creds {
+        let uuid = payload_uuid(&network_seed("wifi", c));
+        let enc = match c.security() {
+            Security::Open | Security::Owe => "None",
+            Security::Wep => "WEP",
+            Security::Wpa2 | Security::Eap => "WPA2",
+            Security::Wpa3 => "WPA3",
+        };
+        payloads.push_str("\t\t<dict>\n");
+        payloads.push_str("\t\t\t<key>AutoJoin</key>\n\t\t\t<true/>\n");
+        payloads.push_str(&format!(
+            "\t\t\t<key>EncryptionType</key>\n\t\t\t<string>{}</string>\n",
+            enc
+        ));
+        payloads.push_str(&format!(
+            "\t\t\t<key>HIDDEN_NETWORK</key>\n\t\t\t<{}/>\n",
+            c.hidden
+        ));
+        if let Some(p) = c.pass.as_deref().filter(|p| !p.is_empty()) {
+            payloads.push_str(&format!(
+                "\t\t\t<key>Password</key>\n\t\t\t<string>{}</string>\n",
+                xml_escape(p)
+            ));
+        }
+        payloads.push_str(&format!(
+            "\t\t\t<key>PayloadDisplayName</key>\n\t\t\t<string>Wi-Fi ({})</string>\n",
+            xml_escape(&c.ssid)
+        ));
+        payloads.push_str(&format!(
+            "\t\t\t<key>PayloadIdentifier</key>\n\t\t\t<string>{}.wifi.{}</string>\n",
+            PROFILE_ID_PREFIX, uuid
+        ));
+        payloads.push_str(
+            "\t\t\t<key>PayloadType</key>\n\t\t\t<string>com.apple.wifi.managed</string>\n",
+        );
+        payloads.push_str(&format!(
+            "\t\t\t<key>PayloadUUID</key>\n\t\t\t<string>{}</string>\n",
+            uuid
+        ));
+        payloads.push_str("\t\t\t<key>PayloadVersion</key>\n\t\t\t<integer>1</integer>\n");
+        payloads.push_str(&format!(
+            "\t\t\t<key>SSID_STR</key>\n\t\t\t<string>{}</string>\n",
+            xml_escape(&c.ssid)
+        ));
+        payloads.push_str("\t\t</dict>\n");
+    }
+
+    let mut ssids: Vec<&str> = creds.iter().map(|c| c.ssid.as_str()).collect();
+    ssids.sort_unstable();
+    let profile_uuid = payload_uuid(&format!("profile:{}", ssids.join("\n")));
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n");
+    xml.push_str("<plist version=\"1.0\">\n<dict>\n");
+    xml.push_str("\t<key>PayloadContent</key>\n\t<array>\n");
+    xml.push_str(&payloads);
+    xml.push_str("\t</array>\n");
+    xml.push_str(&format!(
+        "\t<key>PayloadDisplayName</key>\n\t<string>Wi-Fi networks ({})</string>\n",
+        creds.len()
+    ));
+    xml.push_str(&format!(
+        "\t<key>PayloadIdentifier</key>\n\t<string>{}.profile.{}</string>\n",
+        PROFILE_ID_PREFIX, profile_uuid
+    ));
+    xml.push_str("\t<key>PayloadRemovalDisallowed</key>\n\t<false/>\n");
+    xml.push_str("\t<key>PayloadType</key>\n\t<string>Configuration</string>\n");
+    xml.push_str(&format!(
+        "\t<key>PayloadUUID</key>\n\t<string>{}</string>\n",
+        profile_uuid
+    ));
+    xml.push_str("\t<key>PayloadVersion</key>\n\t<integer>1</integer>\n");
+    xml.push_str("</dict>\n</plist>\n");
+    xml
+}
+
+/// Reads the Wi-Fi payloads of a `.mobileconfig`. Signed profiles work too, since the
+/// plist sits in the CMS envelope as plain text.
+pub fn parse_mobileconfig(text: &str) -> Result<Vec<WifiCred>, ImportError> {
+    const FORMAT: &str = "configuration profile";
+    let start = text
+        .find("<plist")
+        .ok_or_else(|| ImportError::malformed_at(FORMAT, text, 0, "no <plist> element"))?;
+    let end = text[start..]
+        .find("</plist>")
+        .map(|e| start + e)
+        .ok_or_else(|| ImportError::malformed_at(FORMAT, text, text.len(), "unterminated plist"))?;
+    let body = &text[start..end];
+    let body = &body[body.find('>').map(|i| i + 1).unwrap_or(0)..];
+
+    let mut rest = body;
+    let root = parse_plist_value(&mut rest).ok_or_else(|| {
+        let offset = rest.as_ptr() as usize - text.as_ptr() as usize;
+        ImportError::malformed_at(FORMAT, text, offset, "malformed plist value")
+    })?;
+
+    let mut out = Vec::new();
+    collect_wifi_payloads(&root, &mut out);
+    Ok(out)
+}
+
+fn collect_wifi_payloads(v: &PlistValue, out: &mut Vec<WifiCred>) {
+    match v {
+        PlistValue::Dict(entries) => {
+            let get = |k: &str| entries.iter().find(|(key, _)| key == k).map(|(_, v)| v);
+            if let Some(PlistValue::String(t)) = get("PayloadType")
+                && t == "com.apple.wifi.managed"
+            {
+                let Some(PlistValue::String(ssid)) = get("SSID_STR") else {
+                    return;
+                };
+                let pass = match get("Password") {
+                    Some(PlistValue::String(p)) if !p.is_empty() => Some(p.clone()),
+                    _ => None,
+                };
+                let security = match get("EncryptionType") {
+                    Some(PlistValue::String(e)) => match e.as_str() {
+                        "None" => Some(Security::Open),
+                        "WEP" => Some(Security::Wep),
+                        "WPA3" => Some(Security::Wpa3),
+                        "WPA" | "WPA2" => Some(if get("EAPClientConfiguration").is_some() {
+                            Security::Eap
+                        } else {
+                            Security::Wpa2
+                        }),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                out.push(WifiCred {
+                    ssid: ssid.clone(),
+                    pass,
+                    security,
+                    hidden: matches!(get("HIDDEN_NETWORK"), Some(PlistValue::Bool(true))),
+                    ..Default::default()
+                });
+                return;
+            }
+            for (_, v) in entries {
+                collect_wifi_payloads(v, out);
+            }
+        }
+        PlistValue::Array(items) => {
+            for v in items {
+                collect_wifi_payloads(v, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+enum PlistValue {
+    Dict(Vec<(String, PlistValue)>),
+    Array(Vec<PlistValue>),
+    String(String),
+    Bool(bool),
+    Other,
+}
+
+/// Minimal XML plist reader: enough structure to walk profile payloads.
+fn parse_plist_value(s: &mut &str) -> Option<PlistValue> {
+    *s = s.trim_start();
+    while s.starts_with("<!--") {
+        *s = s[s.find("-->")? + 3..].trim_start();
+    }
+    if s.starts_with("<dict/>") {
+        *s = &s[7..];
+        return Some(PlistValue::Dict(Vec::new()));
+    }
+    if s.starts_with("<array/>") {
+        *s = &s[8..];
+        return Some(PlistValue::Array(Vec::new()));
+    }
+    if let Some(r) = s.strip_prefix("<dict>") {
+        *s = r;
+        let mut entries = Vec::new();
+        loop {
+            *s = s.trim_start();
+            if let Some(r) = s.strip_prefix("</dict>") {
+                *s = r;
+                return Some(PlistValue::Dict(entries));
+            }
+            let r = s.strip_prefix("<key>")?;
+            let end = r.find("</key>")?;
+            let key = html_unescape(&r[..end]);
+            *s = &r[end + 6..];
+            entries.push((key, parse_plist_value(s)?));
+        }
+    }
+    if let Some(r) = s.strip_prefix("<array>") {
+        *s = r;
+        let mut items = Vec::new();
+        loop {
+            *s = s.trim_start();
+            if let Some(r) = s.strip_prefix("</array>") {
+                *s = r;
+                return Some(PlistValue::Array(items));
+            }
+            items.push(parse_plist_value(s)?);
+        }
+    }
+    if let Some(r) = s.strip_prefix("<true/>") {
+        *s = r;
+        return Some(PlistValue::Bool(true));
+    }
+    if let Some(r) = s.strip_prefix("<false/>") {
+        *s = r;
+        return Some(PlistValue::Bool(false));
+    }
+    if s.starts_with("<string/>") {
+        *s = &s[9..];
+        return Some(PlistValue::String(String::new()));
+    }
+    // Scalar element: <tag>text</tag>
+    let r = s.strip_prefix('<')?;
+    let tag_end = r.find('>')?;
+    let tag = &r[..tag_end];
+    let close = format!("</{}>", tag);
+    let r = &r[tag_end + 1..];
+    let end = r.find(&close)?;
+    let text = &r[..end];
+    *s = &r[end + close.len()..];
+    Some(if tag == "string" {
+        PlistValue::String(html_unescape(text))
+    } else {
+        PlistValue::Other
+    })
+}
+
+fn find_element(hay: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let i = hay.find(&open)?;
+    let rest = &hay[i + open.len()..];
+    let end = rest.find(&close)?;
+    Some(html_unescape(rest[..end].trim()))
+}
+
+pub fn parse_wifi_configstore_xml(xml: &str) -> Vec<WifiCred> {
+    xml.split("<Network>")
+        .skip(1)
+        .filter_map(|blk| {
+            let end = blk.find("</Network>").unwrap_or(blk.len());
+            configstore_network(&blk[..end])
+        })
+        .collect()
+}
+
+/// One `WifiConfiguration` section of a config store file.
+fn configstore_network(chunk: &str) -> Option<WifiCred> {
+    let (ssid, ssid_hex) = find_string(chunk, "SSID")
+        .map(|s| decode_ssid(&s))
+        .or_else(|| find_string(chunk, "ConfigKey").map(|k| split_raw(&strip_quotes(k))))?;
+    let psk = find_string(chunk, "PreSharedKey").map(|p| restore_text(&strip_quotes(p)));
+    let wep = find_wep_key(chunk).map(|k| restore_text(&k));
+    let security = find_string(chunk, "ConfigKey")
+        .and_then(|k| k.rsplit('"').next().and_then(Security::from_key_mgmt));
+    Some(WifiCred {
+        ssid,
+        ssid_hex,
+        pass: psk.or(wep),
+        security,
+        hidden: find_bool(chunk, "HiddenSSID").unwrap_or(false),
+        stats: configstore_stats(chunk),
+        ..Default::default()
+    })
+}
+
+fn configstore_stats(chunk: &str) -> Option<NetworkStats> {
+    let int = |name| find_value(chunk, "int", name).and_then(|v| v.parse().ok());
+    let stats = NetworkStats {
+        creator_uid: int("CreatorUid"),
+        creator_name: find_string(chunk, "CreatorName"),
+        last_update_uid: int("LastUpdateUid"),
+        last_update_name: find_string(chunk, "LastUpdateName"),
+        creation_time: find_string(chunk, "CreationTime"),
+        update_time: find_string(chunk, "UpdateTime"),
+        last_connected: find_value(chunk, "long", "LastConnectedTime").and_then(|v| v.parse().ok()),
+        has_ever_connected: find_bool(chunk, "HasEverConnected"),
+        selection_status: find_string(chunk, "SelectionStatus"),
+        connect_choice: find_string(chunk, "ConnectChoice"),
+    };
+    (stats != NetworkStats::default()).then_some(stats)
+}
+
+/// The `value` attribute of `<tag name="name" value="…" />`.
+fn find_value(hay: &str, tag: &str, name: &str) -> Option<String> {
+    let needle = format!("<{} name=\"{}\" value=\"", tag, name);
+    let i = hay.find(&needle)?;
+    let rest = &hay[i + needle.len()..];
+    Some(html_unescape(&rest[..rest.find('"')?]))
+}
+
+/// Parses `WifiConfigStoreNetworkSuggestions.xml`: networks apps added through
+/// the suggestion API, grouped per suggesting package.
+pub fn parse_network_suggestions_xml(xml: &str) -> Vec<WifiCred> {
+    let mut out = Vec::new();
+    for app in xml.split("<NetworkSuggestionPerApp>").skip(1) {
+        let end = app.find("</NetworkSuggestionPerApp>").unwrap_or(app.len());
+        let app = &app[..end];
+        let package = find_string(app, "SuggestorPackageName");
+        for blk in app.split("<NetworkSuggestion>").skip(1) {
+            let end = blk.find("</NetworkSuggestion>").unwrap_or(blk.len());
+            if let Some(mut c) = configstore_network(&blk[..end]) {
+                c.suggested_by = package.clone();
+                out.push(c);
+            }
+        }
+    }
+    out
+}
+
+fn find_bool(hay: &str, name: &str) -> Option<bool> {
+    let needle = format!("<boolean name=\"{}\" value=\"", name);
+    let i = hay.find(&needle)?;
+    let rest = &hay[i + needle.len()..];
+    Some(rest.starts_with("true"))
+}
+
+fn find_wep_key(hay: &str) -> Option<String> {
+    let i = hay.find("<string-array name=\"WEPKeys\"")?;
+    let rest = &hay[i..];
+    let end = rest.find("</string-array>").unwrap_or(rest.len());
+    rest[..end]
+        .split("<item value=\"")
+        .skip(1)
+        .filter_map(|item| item.find('"').map(|e| html_unescape(&item[..e])))
+        .map(strip_quotes)
+        .find(|k| !k.is_empty())
+}
+
+fn find_string(hay: &str, name: &str) -> Option<String> {
+    let needle = format!("<string name=\"{}\">", name);
+    let i = hay.find(&needle)?;
+    let start = i + needle.len();
+    let rest = &hay[start..];
+    let end = rest.find("</string>")?;
+    Some(html_unescape(rest[..end].trim()))
+}
+
+fn html_unescape(s: &str) -> String {
+    s.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// A config file SSID, `"quoted"` text or bare hex as Android and wpa_supplicant
+/// store non-text SSIDs, as display text plus, when its bytes aren't UTF-8,
+/// their hex for [`WifiCred::ssid_hex`].
+fn decode_ssid(raw: &str) -> (String, Option<String>) {
+    let t = raw.trim();
+    if t.starts_with('"') {
+        return split_raw(&strip_quotes(t));
+    }
+    match hex_decode(t) {
+        Some(bytes) if !bytes.is_empty() => match String::from_utf8(bytes) {
+            Ok(s) => (s, None),
+            Err(e) => (
+                String::from_utf8_lossy(e.as_bytes()).into_owned(),
+                Some(hex_encode(e.as_bytes())),
+            ),
+        },
+        _ => split_raw(t),
+    }
+}
+
+/// Where [`decode_lossless`] puts undecodable bytes: `U+10FF00 + byte`, in the
+/// Supplementary Private Use Area-B, which config files have no reason to use.
+const RAW_BYTE_BASE: u32 = 0x10_FF00;
+
+/// Decodes a config file without losing bytes: each byte of an invalid UTF-8
+/// sequence becomes one private-use char that [`raw_bytes`] maps back.
+fn decode_lossless(bytes: &[u8]) -> std::borrow::Cow<'_, str> {
+    if let Ok(s) = std::str::from_utf8(bytes) {
+        return s.into();
+    }
+    let mut out = String::with_capacity(bytes.len());
+    for chunk in bytes.utf8_chunks() {
+        out.push_str(chunk.valid());
+        out.extend(
+            chunk
+                .invalid()
+                .iter()
+                .filter_map(|&b| char::from_u32(RAW_BYTE_BASE + b as u32)),
+        );
+    }
+    out.into()
+}
+
+/// The original bytes of text from [`decode_lossless`].
+fn raw_bytes(s: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(s.len());
+    for c in s.chars() {
+        match (c as u32).checked_sub(RAW_BYTE_BASE) {
+            Some(b) if b <= 0xFF => out.push(b as u8),
+            _ => out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+    out
+}
+
+/// [`decode_ssid`] for text that may hold raw bytes.
+fn split_raw(s: &str) -> (String, Option<String>) {
+    match String::from_utf8(raw_bytes(s)) {
+        Ok(s) => (s, None),
+        Err(e) => (
+            String::from_utf8_lossy(e.as_bytes()).into_owned(),
+            Some(hex_encode(e.as_bytes())),
+        ),
+    }
+}
+
+/// Text from [`decode_lossless`] with raw bytes shown as U+FFFD, for fields
+/// that have no hex form.
+fn restore_text(s: &str) -> String {
+    String::from_utf8_lossy(&raw_bytes(s)).into_owned()
+}
+
+fn strip_quotes<S: AsRef<str>>(s: S) -> String {
+    let t = s.as_ref().trim();
+    if t.starts_with('"') && t.ends_with('"') && t.len() >= 2 {
+        t[1..t.len() - 1].to_string()
+    } else {
+        t.to_string()
+    }
+}
+
+pub fn parse_wpa_supplicant(conf: &str) -> Vec<WifiCred> {
+    let mut out = Vec::new();
+    let mut ssid: Option<(String, Option<String>)> = None;
+    let mut psk: Option<String> = None;
+    let mut wep: Option<String> = None;
+    let mut security: Option<Security> = None;
+    let mut hidden = false;
+    let mut in_blk = false;
+
+    for line in conf.lines() {
+        let l = line.trim();
+        if l.starts_with("network={") {
+            in_blk = true;
+            ssid = None;
+            psk = None;
+            wep = None;
+            security = None;
+            hidden = false;
+            continue;
+        }
+        if in_blk && l.starts_with('}') {
+            if let Some((s, hex)) = ssid.take() {
+                if wep.is_some() && security == Some(Security::Open) {
+                    security = Some(Security::Wep);
+                }
+                out.push(WifiCred {
+                    ssid: s,
+                    ssid_hex: hex,
+                    pass: psk.take().or(wep.take()),
+                    security,
+                    hidden,
+                    ..Default::default()
+                });
+            }
+            in_blk = false;
+            continue;
+        }
+        if !in_blk {
+            continue;
+        }
+        if let Some(eq) = l.find('=') {
+            let k = &l[..eq];
+            let v = &l[eq + 1..];
+            match k {
+                "ssid" => ssid = Some(decode_ssid(v)),
+                "psk" => psk = Some(restore_text(&strip_quotes(v))),
+                "wep_key0" => wep = Some(restore_text(&strip_quotes(v))),
+                "key_mgmt" => security = Security::from_key_mgmt(v),
+                "scan_ssid" => hidden = v.trim() == "1",
+                _ => {}
+            }
+        }
+    }
+    out
+}
+
+/// Where the OpenWrt client sections attach.
+#[derive(Clone, Debug)]
+pub struct UciOptions {
+    /// `wifi-device` the clients run on; left out when empty.
+    pub device: String,
+    /// Logical interface they feed; left out when empty.
+    pub network: String,
+    /// Start every section disabled: one radio usually joins a single network.
+    pub disabled: bool,
+}
+
+impl Default for UciOptions {
+    fn default() -> Self {
+        Self {
+            device: "radio0".into(),
+            network: "wwan".into(),
+            disabled: true,
+        }
+    }
+}
+
+/// `config wifi-iface` client sections for OpenWrt's `/etc/config/wireless`.
+///
+/// 802.1X (EAP) networks are left out, as for Windows: a client section needs
+/// the EAP method, identity and certificates, and none of that is exported. The
+/// header comment lists them.
+pub fn build_uci_wireless(creds: &[WifiCred], opts: &UciOptions) -> String {
+    let mut out = String::new();
+    out.push_str("# Client (sta) interfaces for /etc/config/wireless.\n");
+    if opts.disabled {
+        out.push_str("# All start disabled: set `disabled '0'` on the network you want to join.\n");
+    }
+    for c in creds.iter().filter(|c| c.security() == Security::Eap) {
+        out.push_str(&format!("# Skipped 802.1X (EAP) network {:?}\n", c.ssid));
+    }
+    for (i, c) in creds.iter().enumerate() {
+        if c.security() == Security::Eap {
+            continue;
+        }
+        let pass = c.pass.as_deref().unwrap_or("");
+        out.push('\n');
+        out.push_str(&format!("config wifi-iface 'wwan{}'\n", i));
+        if !opts.device.trim().is_empty() {
+            out.push_str(&format!(
+                "\toption device {}\n",
+                uci_quote(opts.device.trim())
+            ));
+        }
+        if !opts.network.trim().is_empty() {
+            out.push_str(&format!(
+                "\toption network {}\n",
+                uci_quote(opts.network.trim())
+            ));
+        }
+        out.push_str("\toption mode 'sta'\n");
+        out.push_str(&format!("\toption ssid {}\n", uci_quote(&c.ssid)));
+        match c.security() {
+            Security::Open => out.push_str("\toption encryption 'none'\n"),
+            Security::Owe => out.push_str("\toption encryption 'owe'\n"),
+            Security::Wep => {
+                out.push_str("\toption encryption 'wep'\n");
+                out.push_str("\toption key '1'\n");
+                // 5/13 characters are ASCII keys; anything else is passed through as hex.
+                let key1 = if matches!(pass.len(), 5 | 13) {
+                    format!("s:{}", pass)
+                } else {
+                    pass.to_string()
+                };
+                out.push_str(&format!("\toption key1 {}\n", uci_quote(&key1)));
+            }
+            Security::Wpa2 => {
+                out.push_str("\toption encryption 'psk2'\n");
+                out.push_str(&format!("\toption key {}\n", uci_quote(pass)));
+            }
+            Security::Wpa3 => {
+                out.push_str("\toption encryption 'sae'\n");
+                out.push_str(&format!("\toption key {}\n", uci_quote(pass)));
+            }
+            Security::Eap => unreachable!("EAP networks are skipped above"),
+        }
+        if c.hidden {
+            out.push_str("\toption hidden '1'\n");
+        }
+        if opts.disabled {
+            out.push_str("\toption disabled '1'\n");
+        }
+    }
+    out
+}
+
+fn uci_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Splits a UCI line into words, honouring single/double quotes and backslash escapes.
+fn uci_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut cur = String::new();
+    let mut in_word = false;
+    let mut chars = line.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '#' if !in_word => break,
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut cur));
+                    in_word = false;
+                }
+            }
+            '\'' => {
+                in_word = true;
+                for c in chars.by_ref() {
+                    if c == '\'' {
+                        break;
+                    }
+                    cur.push(c);
+                }
+            }
+            '"' => {
+                in_word = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => cur.extend(chars.next()),
+                        c => cur.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                cur.extend(chars.next());
+            }
+            c => {
+                in_word = true;
+                cur.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(cur);
+    }
+    words
+}
+
+/// Extracts networks from the `wifi-iface` sections of an OpenWrt wireless config.
+pub fn parse_uci_wireless(conf: &str) -> Vec<WifiCred> {
+    let mut out = Vec::new();
+    let mut section: Option<HashMap<String, String>> = None;
+
+    let flush = |opts: HashMap<String, String>, out: &mut Vec<WifiCred>| {
+        let Some(ssid) = opts.get("ssid").filter(|s| !s.is_empty()) else {
+            return;
+        };
+        let enc = opts.get("encryption").map(String::as_str).unwrap_or("none");
+        let base = enc.split('+').next().unwrap_or(enc);
+        let security = match base {
+            "none" => Security::Open,
+            "owe" => Security::Owe,
+            b if b.starts_with("wep") => Security::Wep,
+            b if b.starts_with("sae") => Security::Wpa3,
+            b if b.starts_with("psk") => Security::Wpa2,
+            b if b.starts_with("wpa") => Security::Eap,
+            _ => Security::Open,
+        };
+        let pass = match security {
+            Security::Wep => {
+                let key = opts.get("key").map(String::as_str).unwrap_or("1");
+                let k = match key {
+                    "1" | "2" | "3" | "4" => opts.get(&format!("key{}", key)).cloned(),
+                    _ => Some(key.to_string()),
+                };
+                k.map(|k| k.strip_prefix("s:").map(str::to_string).unwrap_or(k))
+            }
+            Security::Wpa2 | Security::Wpa3 => opts.get("key").cloned(),
+            _ => None,
+        };
+        out.push(WifiCred {
+            ssid: ssid.clone(),
+            pass: pass.filter(|p| !p.is_empty()),
+            security: Some(security),
+            hidden: opts.get("hidden").map(String::as_str) == Some("1"),
+            ..Default::default()
+        });
+    };
+
+    for line in conf.lines() {
+        let words = uci_words(line);
+        match words.first().map(String::as_str) {
+            Some("config") => {
+                if let Some(opts) = section.take() {
+                    flush(opts, &mut out);
+                }
+                if words.get(1).map(String::as_str) == Some("wifi-iface") {
+                    section = Some(HashMap::new());
+                }
+            }
+            Some("option") if words.len() >= 3 => {
+                if let Some(opts) = section.as_mut() {
+                    opts.insert(words[1].clone(), words[2].clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(opts) = section.take() {
+        flush(opts, &mut out);
+    }
+    out
+}
+
+#[derive(Clone, Debug)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    pub header: bool,
+    pub security: bool,
+    pub hidden: bool,
+    pub notes: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            header: true,
+            security: true,
+            hidden: true,
+            notes: false,
+        }
+    }
+}
+
+/// RFC 4180 CSV: CRLF rows, fields quoted when they contain the delimiter, quotes or newlines.
+pub fn build_csv(creds: &[WifiCred], opts: &CsvOptions) -> String {
+    let mut out = String::new();
+    let mut push_row = |cells: Vec<String>| {
+        let row: Vec<String> = cells.iter().map(|c| csv_field(c, opts.delimiter)).collect();
+        out.push_str(&row.join(&opts.delimiter.to_string()));
+        out.push_str("\r\n");
+    };
+
+    if opts.header {
+        let mut h = vec!["ssid".to_string(), "password".to_string()];
+        if opts.security {
+            h.push("security".into());
+        }
+        if opts.hidden {
+            h.push("hidden".into());
+        }
+        if opts.notes {
+            h.push("notes".into());
+        }
+        push_row(h);
+    }
+    for c in creds {
+        let mut row = vec![c.ssid.clone(), c.pass.clone().unwrap_or_default()];
+        if opts.security {
+            row.push(c.security().as_str().into());
+        }
+        if opts.hidden {
+            row.push(c.hidden.to_string());
+        }
+        if opts.notes {
+            row.push(c.notes.clone().unwrap_or_default());
+        }
+        push_row(row);
+    }
+    out
+}
+
+fn csv_field(s: &str, delimiter: char) -> String {
+    if s.contains(delimiter) || s.contains(['"', '\r', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Splits CSV text into records per RFC 4180 (quoted fields may span lines).
+fn csv_records(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
                 }
+            } else {
+                field.push(c);
+            }
+        } else if c == '"' && field.is_empty() {
+            in_quotes = true;
+        } else if c == delimiter {
+            record.push(std::mem::take(&mut field));
+        } else if c == '\r' || c == '\n' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            record.push(std::mem::take(&mut field));
+            if !(record.len() == 1 && record[0].is_empty()) {
+                records.push(std::mem::take(&mut record));
             }
+            record.clear();
+        } else {
+            field.push(c);
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+/// Which CSV column feeds which `WifiCred` field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvMapping {
+    pub delimiter: char,
+    pub has_header: bool,
+    pub ssid: usize,
+    pub pass: Option<usize>,
+    pub security: Option<usize>,
+    pub hidden: Option<usize>,
+    pub notes: Option<usize>,
+}
+
+impl CsvMapping {
+    /// Positional mapping for header-less files: ssid, password, security, hidden, notes.
+    fn positional(delimiter: char, columns: usize) -> Self {
+        let col = |i: usize| (i < columns).then_some(i);
+        Self {
+            delimiter,
+            has_header: false,
+            ssid: 0,
+            pass: col(1),
+            security: col(2),
+            hidden: col(3),
+            notes: col(4),
+        }
+    }
+}
+
+pub const CSV_DELIMITERS: [char; 4] = [',', ';', '\t', '|'];
+
+/// Records the delimiter sniffer looks at.
+const CSV_SNIFF_RECORDS: usize = 20;
+
+/// Picks the delimiter that splits the first records into the same number of
+/// columns most often. Quotes are honoured, so a quoted `,` in an SSID doesn't
+/// count and quoted line breaks don't start a new record.
+fn sniff_csv_delimiter(text: &str) -> Option<char> {
+    CSV_DELIMITERS
+        .into_iter()
+        .filter_map(|d| {
+            let records = csv_records(text, d);
+            let records = &records[..records.len().min(CSV_SNIFF_RECORDS)];
+            let width = records.first()?.len();
+            let consistent = records.iter().filter(|r| r.len() == width).count();
+            (width > 1).then_some((d, (consistent, width)))
+        })
+        .fold(
+            None,
+            |best: Option<(char, (usize, usize))>, cur| match best {
+                Some((_, score)) if score >= cur.1 => best,
+                _ => Some(cur),
+            },
+        )
+        .map(|(d, _)| d)
+}
+
+/// Guesses delimiter and column mapping. Returns `None` when the text doesn't
+/// look like a table with at least an SSID column.
+pub fn detect_csv_mapping(text: &str) -> Option<CsvMapping> {
+    csv_mapping_for(text, sniff_csv_delimiter(text)?)
+}
+
+/// Column mapping for `text` split on `delimiter`, from its header row if it
+/// has one, else positional.
+pub fn csv_mapping_for(text: &str, delimiter: char) -> Option<CsvMapping> {
+    let records = csv_records(text, delimiter);
+    let header = records.first()?;
+
+    let find = |names: &[&str]| {
+        header.iter().position(|h| {
+            let h = h.trim().to_ascii_lowercase();
+            names.iter().any(|n| h == *n)
+        })
+    };
+    let ssid = find(&["ssid", "network", "network name", "wifi", "wi-fi", "name"]);
+    match ssid {
+        Some(ssid) => Some(CsvMapping {
+            delimiter,
+            has_header: true,
+            ssid,
+            pass: find(&["password", "pass", "psk", "key", "passphrase"]),
+            security: find(&["security", "auth", "authentication", "encryption", "type"]),
+            hidden: find(&["hidden", "hidden ssid"]),
+            notes: find(&["notes", "note", "comment", "comments", "description"]),
+        }),
+        None => Some(CsvMapping::positional(delimiter, header.len())),
+    }
+}
+
+/// Column titles for a mapping picker: the header cells, or "Column N".
+pub fn csv_columns(text: &str, mapping: &CsvMapping) -> Vec<String> {
+    let records = csv_records(text, mapping.delimiter);
+    let width = records
+        .iter()
+        .take(CSV_SNIFF_RECORDS)
+        .map(Vec::len)
+        .max()
+        .unwrap_or(0);
+    (0..width)
+        .map(|i| {
+            records
+                .first()
+                .filter(|_| mapping.has_header)
+                .and_then(|h| h.get(i))
+                .map(|h| h.trim())
+                .filter(|h| !h.is_empty())
+                .map_or_else(|| format!("Column {}", i + 1), str::to_string)
+        })
+        .collect()
+}
+
+pub fn parse_csv(text: &str, mapping: &CsvMapping) -> Vec<WifiCred> {
+    let records = csv_records(text, mapping.delimiter);
+    let skip = usize::from(mapping.has_header);
+    let cell = |r: &[String], col: Option<usize>| {
+        col.and_then(|c| r.get(c))
+            .map(|v| v.to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    records
+        .iter()
+        .skip(skip)
+        .filter_map(|r| {
+            let ssid = cell(r, Some(mapping.ssid))?;
+            Some(WifiCred {
+                ssid,
+                pass: cell(r, mapping.pass),
+                security: cell(r, mapping.security).and_then(|s| Security::from_label(&s)),
+                hidden: cell(r, mapping.hidden).is_some_and(|h| {
+                    matches!(
+                        h.trim().to_ascii_lowercase().as_str(),
+                        "true" | "1" | "yes" | "y"
+                    )
+                }),
+                notes: cell(r, mapping.notes),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Folder / group / tag name used to mark Wi-Fi entries in password-manager exports.
+const VAULT_FOLDER: &str = "Wi-Fi";
+
+fn is_wifi_label(s: &str) -> bool {
+    matches!(
+        s.trim()
+            .to_ascii_lowercase()
+            .replace(['-', ' '], "")
+            .as_str(),
+        "wifi" | "wifinetworks" | "wireless"
+    )
+}
+
+/// Unencrypted Bitwarden JSON: one login item per network (username = SSID) in a
+/// "Wi-Fi" folder, with SSID/security/hidden custom fields.
+pub fn build_bitwarden_json(creds: &[WifiCred]) -> String {
+    let folder_id = stable_uuid("bitwarden-folder").to_string();
+    let items: Vec<serde_json::Value> = creds
+        .iter()
+        .map(|c| {
+            serde_json::json!({
+                "id": stable_uuid(&network_seed("bitwarden", c)).to_string(),
+                "organizationId": null,
+                "folderId": folder_id,
+                "type": 1,
+                "reprompt": 0,
+                "name": c.ssid,
+                "notes": c.notes,
+                "favorite": false,
+                "fields": [
+                    { "name": "SSID", "value": c.ssid, "type": 0, "linkedId": null },
+                    { "name": "Security", "value": c.security().as_str(), "type": 0, "linkedId": null },
+                    { "name": "Hidden", "value": c.hidden.to_string(), "type": 2, "linkedId": null },
+                ],
+                "login": {
+                    "uris": [],
+                    "username": c.ssid,
+                    "password": c.pass,
+                    "totp": null,
+                },
+                "collectionIds": null,
+            })
+        })
+        .collect();
+    let doc = serde_json::json!({
+        "encrypted": false,
+        "folders": [{ "id": folder_id, "name": VAULT_FOLDER }],
+        "items": items,
+    });
+    serde_json::to_string_pretty(&doc).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// Picks the Wi-Fi items out of a Bitwarden JSON export: items in a Wi-Fi folder or
+/// carrying an `SSID` custom field.
+pub fn parse_bitwarden_json(json: &str) -> Result<Vec<WifiCred>, ImportError> {
+    let doc: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| ImportError::malformed_json("Bitwarden JSON", &e))?;
+    if doc.get("encrypted").and_then(|v| v.as_bool()) == Some(true) {
+        return Err(ImportError::Unsupported(
+            "Encrypted Bitwarden exports are not supported".into(),
+        ));
+    }
+    let wifi_folders: Vec<&str> = doc
+        .get("folders")
+        .and_then(|f| f.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|f| {
+            f.get("name")
+                .and_then(|n| n.as_str())
+                .is_some_and(is_wifi_label)
+        })
+        .filter_map(|f| f.get("id").and_then(|i| i.as_str()))
+        .collect();
+
+    let mut out = Vec::new();
+    for item in doc
+        .get("items")
+        .and_then(|i| i.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let str_at = |v: &serde_json::Value, k: &str| {
+            v.get(k)
+                .and_then(|x| x.as_str())
+                .filter(|x| !x.is_empty())
+                .map(str::to_string)
+        };
+        let field = |name: &str| {
+            item.get("fields")
+                .and_then(|f| f.as_array())
+                .into_iter()
+                .flatten()
+                .find(|f| {
+                    f.get("name")
+                        .and_then(|n| n.as_str())
+                        .is_some_and(|n| n.eq_ignore_ascii_case(name))
+                })
+                .and_then(|f| str_at(f, "value"))
+        };
+        let in_folder = item
+            .get("folderId")
+            .and_then(|f| f.as_str())
+            .is_some_and(|f| wifi_folders.contains(&f));
+        let ssid_field = field("SSID");
+        if !in_folder && ssid_field.is_none() {
+            continue;
+        }
+
+        let login = item.get("login");
+        let Some(ssid) = ssid_field
+            .or_else(|| login.and_then(|l| str_at(l, "username")))
+            .or_else(|| str_at(item, "name"))
+        else {
+            continue;
+        };
+        out.push(WifiCred {
+            ssid,
+            pass: login
+                .and_then(|l| str_at(l, "password"))
+                .or_else(|| field("Password")),
+            security: field("Security").and_then(|s| Security::from_label(&s)),
+            hidden: field("Hidden").as_deref() == Some("true"),
+            notes: str_at(item, "notes"),
+            ..Default::default()
+        });
+    }
+    Ok(out)
+}
+
+fn keepass_string(key: &str, value: &str, protect: bool) -> String {
+    format!(
+        "\t\t\t\t<String>\n\t\t\t\t\t<Key>{}</Key>\n\t\t\t\t\t<Value{}>{}</Value>\n\t\t\t\t</String>\n",
+        key,
+        if protect {
+            " ProtectInMemory=\"True\""
         } else {
-            warn!("Could not read {}", p);
+            ""
+        },
+        xml_escape(value)
+    )
+}
+
+fn keepass_uuid(seed: &str) -> String {
+    B64.encode(stable_uuid(seed).as_bytes())
+}
+
+/// KeePass 2 XML (the "KeePass XML (2.x)" import format): a "Wi-Fi" group with one
+/// entry per network, tagged `wifi`.
+pub fn build_keepass_xml(creds: &[WifiCred]) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\" standalone=\"yes\"?>\n");
+    xml.push_str("<KeePassFile>\n\t<Meta>\n\t\t<Generator>WiFi Exporter</Generator>\n\t</Meta>\n");
+    xml.push_str("\t<Root>\n\t\t<Group>\n");
+    xml.push_str(&format!(
+        "\t\t\t<UUID>{}</UUID>\n\t\t\t<Name>{}</Name>\n",
+        keepass_uuid("keepass-group"),
+        VAULT_FOLDER
+    ));
+    for c in creds {
+        xml.push_str("\t\t\t<Entry>\n");
+        xml.push_str(&format!(
+            "\t\t\t\t<UUID>{}</UUID>\n\t\t\t\t<Tags>wifi</Tags>\n",
+            keepass_uuid(&network_seed("keepass", c))
+        ));
+        xml.push_str(&keepass_string("Title", &c.ssid, false));
+        xml.push_str(&keepass_string("UserName", &c.ssid, false));
+        xml.push_str(&keepass_string(
+            "Password",
+            c.pass.as_deref().unwrap_or(""),
+            true,
+        ));
+        xml.push_str(&keepass_string("SSID", &c.ssid, false));
+        xml.push_str(&keepass_string("Security", c.security().as_str(), false));
+        xml.push_str(&keepass_string("Hidden", &c.hidden.to_string(), false));
+        xml.push_str(&keepass_string(
+            "Notes",
+            c.notes.as_deref().unwrap_or(""),
+            false,
+        ));
+        xml.push_str("\t\t\t</Entry>\n");
+    }
+    xml.push_str("\t\t</Group>\n\t</Root>\n</KeePassFile>\n");
+    xml
+}
+
+/// Reads the `<String>` key/value pairs of one KeePass entry.
+fn keepass_strings(entry: &str) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    for blk in entry.split("<String>").skip(1) {
+        let end = blk.find("</String>").unwrap_or(blk.len());
+        let blk = &blk[..end];
+        let Some(key) = find_element(blk, "Key") else {
+            continue;
+        };
+        let value = blk
+            .find("<Value")
+            .map(|i| &blk[i..])
+            .filter(|v| !v.starts_with("<Value/>"))
+            .and_then(|v| {
+                let start = v.find('>')? + 1;
+                let end = v.find("</Value>")?;
+                Some(html_unescape(&v[start..end]))
+            })
+            .unwrap_or_default();
+        map.insert(key, value);
+    }
+    map
+}
+
+/// Picks Wi-Fi entries out of a KeePass 2 XML export: entries with an `SSID` field,
+/// a `wifi` tag, or sitting in a Wi-Fi group.
+pub fn parse_keepass_xml(xml: &str) -> Vec<WifiCred> {
+    // Drop entry history so old revisions don't show up as extra networks.
+    let mut doc = String::with_capacity(xml.len());
+    let mut rest = xml;
+    while let Some(i) = rest.find("<History>") {
+        doc.push_str(&rest[..i]);
+        rest = rest[i..]
+            .find("</History>")
+            .map(|e| &rest[i + e + "</History>".len()..])
+            .unwrap_or("");
+    }
+    doc.push_str(rest);
+
+    const MARKERS: [&str; 3] = ["<Group>", "</Group>", "<Entry>"];
+    let next_marker = |from: usize| {
+        MARKERS
+            .iter()
+            .filter_map(|m| doc[from..].find(m).map(|i| (from + i, *m)))
+            .min_by_key(|(i, _)| *i)
+    };
+
+    let mut out = Vec::new();
+    // Names of the groups around the current position, outermost first.
+    let mut groups: Vec<Option<String>> = Vec::new();
+    let mut pos = 0;
+    while let Some((start, marker)) = next_marker(pos) {
+        let body = start + marker.len();
+        if marker == "</Group>" {
+            groups.pop();
+            pos = body;
+            continue;
+        }
+        if marker == "<Group>" {
+            // A group's own `<Name>` comes before its first subgroup or entry.
+            let head_end = next_marker(body).map_or(doc.len(), |(i, _)| i);
+            groups.push(find_element(&doc[body..head_end], "Name"));
+            pos = body;
+            continue;
         }
+        let end = doc[body..]
+            .find("</Entry>")
+            .map(|e| body + e)
+            .unwrap_or(doc.len());
+        let entry = &doc[body..end];
+        pos = end;
+
+        let tags = find_element(entry, "Tags").unwrap_or_default();
+        let fields = keepass_strings(entry);
+        let nonempty = |k: &str| fields.get(k).filter(|v| !v.is_empty()).cloned();
+
+        let is_wifi = fields.contains_key("SSID")
+            || tags.split([',', ';', ' ']).any(is_wifi_label)
+            || groups.iter().flatten().any(|g| is_wifi_label(g));
+        if !is_wifi {
+            continue;
+        }
+        let Some(ssid) = nonempty("SSID")
+            .or_else(|| nonempty("UserName"))
+            .or_else(|| nonempty("Title"))
+        else {
+            continue;
+        };
+        out.push(WifiCred {
+            ssid,
+            pass: nonempty("Password"),
+            security: nonempty("Security").and_then(|s| Security::from_label(&s)),
+            hidden: nonempty("Hidden").as_deref() == Some("true"),
+            notes: nonempty("Notes"),
+            ..Default::default()
+        });
     }
-    Err(anyhow::anyhow!("No readable Wi‑Fi config via su"))
+    out
 }
+
+const ONEPASSWORD_HEADER: [&str; 6] = ["Title", "Url", "Username", "Password", "Tags", "Notes"];
+
+/// 1Password CSV import layout. Security and hidden flag go into the notes as
+/// `key: value` lines so they survive a round trip.
+pub fn build_1password_csv(creds: &[WifiCred]) -> String {
+    let mut out = String::new();
+    let row = |cells: &[&str]| {
+        let cells: Vec<String> = cells.iter().map(|c| csv_field(c, ',')).collect();
+        cells.join(",") + "\r\n"
+    };
+    out.push_str(&row(&ONEPASSWORD_HEADER));
+    for c in creds {
+        let mut notes = format!("ssid: {}\nsecurity: {}", c.ssid, c.security().as_str());
+        if c.hidden {
+            notes.push_str("\nhidden: true");
+        }
+        if let Some(n) = c.notes.as_deref() {
+            notes.push_str("\n\n");
+            notes.push_str(n);
+        }
+        out.push_str(&row(&[
+            &c.ssid,
+            "",
+            &c.ssid,
+            c.pass.as_deref().unwrap_or(""),
+            VAULT_FOLDER,
+            &notes,
+        ]));
+    }
+    out
+}
+
+fn is_1password_csv(text: &str) -> bool {
+    let Some(first) = text.trim_start_matches('\u{feff}').lines().next() else {
+        return false;
+    };
+    let cols: Vec<String> = first
+        .split(',')
+        .map(|c| c.trim().trim_matches('"').to_ascii_lowercase())
+        .collect();
+    let has = |n: &str| cols.iter().any(|c| c == n);
+    has("title") && has("password") && (has("url") || has("website") || has("tags"))
+}
+
+/// Picks Wi-Fi rows out of a 1Password CSV: rows tagged Wi-Fi or carrying our
+/// `ssid:` / `security:` note lines.
+pub fn parse_1password_csv(text: &str) -> Vec<WifiCred> {
+    let records = csv_records(text, ',');
+    let Some(header) = records.first() else {
+        return Vec::new();
+    };
+    let col = |name: &str| {
+        header
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name))
+    };
+    let (title, user, pass, tags, notes) = (
+        col("title"),
+        col("username"),
+        col("password"),
+        col("tags"),
+        col("notes"),
+    );
+
+    let mut out = Vec::new();
+    for r in records.iter().skip(1) {
+        let cell = |c: Option<usize>| c.and_then(|i| r.get(i)).filter(|v| !v.is_empty()).cloned();
+        let note = cell(notes).unwrap_or_default();
+        let mut meta: HashMap<String, String> = HashMap::new();
+        let mut free_notes = Vec::new();
+        for line in note.lines() {
+            match line.split_once(": ") {
+                Some((k, v)) if ["ssid", "security", "hidden"].contains(&k) => {
+                    meta.insert(k.to_string(), v.to_string());
+                }
+                _ => free_notes.push(line),
+            }
+        }
+        let tagged = cell(tags).is_some_and(|t| t.split([',', ';']).any(is_wifi_label));
+        if !tagged && !meta.contains_key("ssid") && !meta.contains_key("security") {
+            continue;
+        }
+        let Some(ssid) = meta
+            .get("ssid")
+            .cloned()
+            .or_else(|| cell(user))
+            .or_else(|| cell(title))
+        else {
+            continue;
+        };
+        let free = free_notes.join("\n").trim().to_string();
+        out.push(WifiCred {
+            ssid,
+            pass: cell(pass),
+            security: meta.get("security").and_then(|s| Security::from_label(s)),
+            hidden: meta.get("hidden").map(String::as_str) == Some("true"),
+            notes: (!free.is_empty()).then_some(free),
+            ..Default::default()
+        });
+    }
+    out
+}
+
+/// Splits a `WIFI:` URI body on unescaped `;`, unescaping `\;`, `\,`, `\:`, `\\` and `\"`.
+fn wifi_uri_fields(body: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut cur = String::new();
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(n) = chars.next() {
+                    cur.push(n);
+                }
+            }
+            ';' => fields.push(std::mem::take(&mut cur)),
+            c => cur.push(c),
+        }
+    }
+    if !cur.is_empty() {
+        fields.push(cur);
+    }
+    fields
+}
+
+/// Reads `WIFI:S:<ssid>;T:<WPA|WEP|SAE|nopass>;P:<pass>;H:<true>;;` URIs (the Wi‑Fi QR
+/// code payload), one per line.
+pub fn parse_wifi_uris(text: &str) -> Vec<WifiCred> {
+    let mut out = Vec::new();
+    for line in text.lines() {
+        let Some(body) = line.trim().strip_prefix("WIFI:") else {
+            continue;
+        };
+        let mut cred = WifiCred::default();
+        let mut kind = None;
+        for field in wifi_uri_fields(body) {
+            let Some((k, v)) = field.split_once(':') else {
+                continue;
+            };
+            match k {
+                "S" => cred.ssid = v.to_string(),
+                "P" if !v.is_empty() => cred.pass = Some(v.to_string()),
+                "T" => kind = Security::from_label(v),
+                "H" => cred.hidden = v.eq_ignore_ascii_case("true"),
+                _ => {}
+            }
+        }
+        if cred.ssid.is_empty() {
+            continue;
+        }
+        // `T:WPA` covers every PSK generation; leave it to the password guess.
+        cred.security = kind.filter(|s| *s != Security::Wpa2);
+        out.push(cred);
+    }
+    out
+}
+
+/// NetworkManager stores `ssid=` either as text or as `;`-separated byte values.
+fn nm_ssid(v: &str) -> String {
+    let parts: Vec<&str> = v.trim_end_matches(';').split(';').collect();
+    if parts.len() > 1 && parts.iter().all(|p| p.parse::<u8>().is_ok()) {
+        let bytes: Vec<u8> = parts.iter().filter_map(|p| p.parse().ok()).collect();
+        return String::from_utf8_lossy(&bytes).into_owned();
+    }
+    v.to_string()
+}
+
+/// Reads NetworkManager `.nmconnection` keyfiles; several may be concatenated.
+pub fn parse_nm_keyfiles(text: &str) -> Vec<WifiCred> {
+    let mut out = Vec::new();
+    let mut cur: Option<WifiCred> = None;
+    let mut is_wifi = false;
+    let mut key_mgmt: Option<String> = None;
+    let mut section = String::new();
+
+    let mut finish = |cur: &mut Option<WifiCred>, is_wifi: bool, km: &mut Option<String>| {
+        if let Some(mut c) = cur.take()
+            && is_wifi
+            && !c.ssid.is_empty()
+        {
+            c.security = Some(match km.take().as_deref() {
+                None => Security::Open,
+                Some("none") => Security::Wep,
+                Some(km) => Security::from_key_mgmt(km).unwrap_or(Security::Wpa2),
+            });
+            out.push(c);
+        }
+        *km = None;
+    };
+
+    for line in text.lines() {
+        let l = line.trim();
+        if l.is_empty() || l.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = l.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            section = name.to_string();
+            if section == "connection" {
+                finish(&mut cur, is_wifi, &mut key_mgmt);
+                cur = Some(WifiCred::default());
+                is_wifi = false;
+            }
+            continue;
+        }
+        let (Some(c), Some((k, v))) = (cur.as_mut(), l.split_once('=')) else {
+            continue;
+        };
+        let (k, v) = (k.trim(), v.trim());
+        match (section.as_str(), k) {
+            ("connection", "type") => is_wifi = v == "wifi" || v == "802-11-wireless",
+            ("wifi" | "802-11-wireless", "ssid") => c.ssid = nm_ssid(v),
+            ("wifi" | "802-11-wireless", "hidden") => c.hidden = v == "true",
+            ("wifi-security" | "802-11-wireless-security", "key-mgmt") => {
+                key_mgmt = Some(v.to_ascii_lowercase())
+            }
+            ("wifi-security" | "802-11-wireless-security", "psk" | "wep-key0") => {
+                c.pass = Some(v.to_string())
+            }
+            _ => {}
+        }
+    }
+    finish(&mut cur, is_wifi, &mut key_mgmt);
+    out
+}
+
+fn sniff(bytes: &[u8]) -> std::borrow::Cow<'_, str> {
+    String::from_utf8_lossy(bytes)
+}
+
+fn utf8(bytes: &[u8]) -> Result<&str, ImportError> {
+    std::str::from_utf8(bytes).map_err(|e| {
+        let valid = std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or_default();
+        ImportError::malformed_at("text", valid, valid.len(), "not valid UTF-8")
+    })
+}
+
+/// One import format: a content sniffer and its parser.
+pub struct ImportReader {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// How sure we are `bytes` is this format: 0 = not it, 100 = certain.
+    pub detect: fn(&[u8]) -> u8,
+    pub parse: fn(&[u8]) -> Result<Vec<WifiCred>, ImportError>,
+}
+
+/// Every format `Load File` and `Load System` understand. On equal scores the
+/// earlier entry wins.
+pub static IMPORT_READERS: &[ImportReader] = &[
+    ImportReader {
+        id: "json",
+        name: "WiFi Exporter JSON",
+        detect: |b| {
+            let t = sniff(b);
+            let t = t.trim_start();
+            if !t.starts_with(['[', '{']) {
+                0
+            } else if t.contains("\"format_version\"") {
+                95
+            } else if t.contains("\"ssid\"") {
+                70
+            } else {
+                50
+            }
+        },
+        parse: |b| parse_imported_json(utf8(b)?),
+    },
+    ImportReader {
+        id: "suggestions",
+        name: "Android network suggestions",
+        detect: |b| {
+            if sniff(b).contains("<NetworkSuggestionPerApp>") {
+                97
+            } else {
+                0
+            }
+        },
+        parse: |b| {
+            require_networks(
+                parse_network_suggestions_xml(&decode_lossless(b)),
+                "WifiConfigStoreNetworkSuggestions",
+            )
+        },
+    },
+    ImportReader {
+        id: "configstore",
+        name: "Android WifiConfigStore",
+        detect: |b| {
+            let t = sniff(b);
+            if t.contains("<WifiConfigStoreData") {
+                95
+            } else if t.contains("<Network>") {
+                60
+            } else {
+                0
+            }
+        },
+        // System configs are read whole and decoded without loss: one stray byte
+        // neither loses every network nor the raw bytes of an SSID.
+        parse: |b| {
+            require_networks(
+                parse_wifi_configstore_xml(&decode_lossless(b)),
+                "WifiConfigStore",
+            )
+        },
+    },
+    ImportReader {
+        id: "wpa_supplicant",
+        name: "wpa_supplicant.conf",
+        detect: |b| {
+            if sniff(b).contains("network={") {
+                85
+            } else {
+                0
+            }
+        },
+        parse: |b| {
+            require_networks(
+                parse_wpa_supplicant(&decode_lossless(b)),
+                "wpa_supplicant config",
+            )
+        },
+    },
+    ImportReader {
+        id: "mobileconfig",
+        name: "Apple configuration profile",
+        detect: |b| {
+            let t = sniff(b);
+            if t.contains("com.apple.wifi.managed") {
+                95
+            } else if t.contains("<plist") {
+                30
+            } else {
+                0
+            }
+        },
+        parse: |b| parse_mobileconfig(utf8(b)?),
+    },
+    ImportReader {
+        id: "uci",
+        name: "OpenWrt UCI wireless",
+        detect: |b| {
+            if sniff(b).contains("config wifi-iface") {
+                90
+            } else {
+                0
+            }
+        },
+        parse: |b| require_networks(parse_uci_wireless(utf8(b)?), "UCI wireless config"),
+    },
+    ImportReader {
+        id: "wlan",
+        name: "Windows WLAN profile",
+        detect: |b| {
+            if sniff(b).contains("<WLANProfile") {
+                95
+            } else {
+                0
+            }
+        },
+        parse: |b| require_networks(parse_wlan_profiles(utf8(b)?), "WLAN profile"),
+    },
+    ImportReader {
+        id: "keepass",
+        name: "KeePass XML",
+        detect: |b| {
+            if sniff(b).contains("<KeePassFile") {
+                95
+            } else {
+                0
+            }
+        },
+        parse: |b| require_networks(parse_keepass_xml(utf8(b)?), "KeePass export"),
+    },
+    ImportReader {
+        id: "bitwarden",
+        name: "Bitwarden JSON",
+        detect: |b| {
+            let t = sniff(b);
+            if !t.trim_start().starts_with('{') || !t.contains("\"items\"") {
+                0
+            } else if t.contains("\"encrypted\"") {
+                90
+            } else if t.contains("\"folders\"") {
+                75
+            } else {
+                0
+            }
+        },
+        parse: |b| require_networks(parse_bitwarden_json(utf8(b)?)?, "Bitwarden export"),
+    },
+    ImportReader {
+        id: "1password",
+        name: "1Password CSV",
+        detect: |b| if is_1password_csv(&sniff(b)) { 85 } else { 0 },
+        parse: |b| require_networks(parse_1password_csv(utf8(b)?), "1Password CSV"),
+    },
+    ImportReader {
+        id: "wifi_uri",
+        name: "Wi‑Fi QR code URI",
+        detect: |b| {
+            let t = sniff(b);
+            if t.lines().any(|l| l.trim_start().starts_with("WIFI:")) {
+                90
+            } else {
+                0
+            }
+        },
+        parse: |b| require_networks(parse_wifi_uris(utf8(b)?), "Wi‑Fi URI list"),
+    },
+    ImportReader {
+        id: "nmconnection",
+        name: "NetworkManager keyfile",
+        detect: |b| {
+            let t = sniff(b);
+            let wifi = t.contains("[wifi]") || t.contains("[802-11-wireless]");
+            match (t.contains("[connection]"), wifi) {
+                (true, true) => 90,
+                (true, false) => 20,
+                _ => 0,
+            }
+        },
+        parse: |b| require_networks(parse_nm_keyfiles(utf8(b)?), "NetworkManager keyfile"),
+    },
+    ImportReader {
+        id: "csv",
+        name: "CSV",
+        detect: |b| {
+            let t = sniff(b);
+            if t.trim_start().starts_with(['[', '{', '<']) || detect_csv_mapping(&t).is_none() {
+                0
+            } else {
+                40
+            }
+        },
+        parse: |b| {
+            let text = utf8(b)?;
+            let mapping = detect_csv_mapping(text).ok_or(ImportError::NoNetworks("CSV"))?;
+            info!("CSV import with columns {:?}", mapping);
+            require_networks(parse_csv(text, &mapping), "CSV")
+        },
+    },
+];
+
+/// Picks the reader most confident about `bytes`, with its score.
+pub fn detect_reader(bytes: &[u8]) -> Option<(&'static ImportReader, u8)> {
+    IMPORT_READERS
+        .iter()
+        .map(|r| (r, (r.detect)(bytes)))
+        .filter(|(_, score)| *score > 0)
+        .fold(None, |best, cur| match best {
+            Some((_, s)) if s >= cur.1 => best,
+            _ => Some(cur),
+        })
+}
+
+/// Where Android and OEM builds keep saved networks, newest layout first. Shell
+/// globs; `{user}` expands to each Android user id.
+pub const SYSTEM_CONFIG_PATHS: &[&str] = &[
+    "/data/misc/apexdata/com.android.wifi/WifiConfigStore.xml",
+    "/data/misc_ce/{user}/apexdata/com.android.wifi/WifiConfigStore.xml",
+    "/data/misc/wifi/WifiConfigStore.xml",
+    "/data/misc_ce/{user}/wifi/WifiConfigStore.xml",
+    "/data/misc/wifi/WifiConfigStore.conf",
+    "/data/misc/wifi/wpa_supplicant.conf",
+    "/data/vendor/wifi/wpa/wpa_supplicant.conf",
+    // App suggestions; a saved copy of the same network wins, see `merge_system_networks`.
+    "/data/misc_ce/{user}/apexdata/com.android.wifi/WifiConfigStoreNetworkSuggestions.xml",
+    "/data/misc_ce/{user}/wifi/WifiConfigStoreNetworkSuggestions.xml",
+];
+
+/// File names discovery mode looks for under `/data/misc*`.
+const DISCOVER_NAMES: [&str; 3] = [
+    "WifiConfigStore*.xml",
+    "WifiConfigStore*.conf",
+    "wpa_supplicant*.conf",
+];
+
+/// Where "Load System" looks for config files.
+#[derive(Clone, Debug)]
+pub struct ConfigSearch {
+    /// Paths or shell globs, in priority order.
+    pub paths: Vec<String>,
+    /// Also `find` files named like [`DISCOVER_NAMES`] under `/data/misc*`.
+    pub discover: bool,
+}
+
+impl Default for ConfigSearch {
+    fn default() -> Self {
+        ConfigSearch {
+            paths: SYSTEM_CONFIG_PATHS.iter().map(|p| p.to_string()).collect(),
+            discover: false,
+        }
+    }
+}
+
+/// Paths go to the shell unquoted so globs expand; anything beyond plain path
+/// characters, `*?[]` and the `{user}` placeholder is refused.
+pub fn is_path_pattern(p: &str) -> bool {
+    p.starts_with('/')
+        && p.replace("{user}", "0")
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/._-+@*?[]".contains(c))
+}
+
+/// `pattern` with `{user}` replaced by each of `users`, or by `*` when the
+/// user list is unknown.
+pub fn expand_user(pattern: &str, users: Option<&[u32]>) -> Vec<String> {
+    match users {
+        Some(ids) if pattern.contains("{user}") => ids
+            .iter()
+            .map(|id| pattern.replace("{user}", &id.to_string()))
+            .collect(),
+        _ => vec![pattern.replace("{user}", "*")],
+    }
+}
+
+/// The user id in per-user paths like `/data/misc_ce/10/…`; `None` for shared ones.
+pub fn owning_user(path: &str) -> Option<u32> {
+    [
+        "/data/misc_ce/",
+        "/data/misc_de/",
+        "/data/user/",
+        "/data/user_de/",
+    ]
+    .iter()
+    .find_map(|prefix| path.strip_prefix(prefix))
+    .and_then(|rest| rest.split('/').next())
+    .and_then(|id| id.parse().ok())
+}
+
+/// Existing config files matched by `search` for `users`, deduplicated, in
+/// search order.
+pub fn find_config_files(
+    search: &ConfigSearch,
+    users: Option<&[u32]>,
+) -> Result<Vec<String>, SuError> {
+    let mut script = String::new();
+    for p in &search.paths {
+        if !is_path_pattern(p) {
+            warn!("Skipping config path with unsupported characters: {}", p);
+            continue;
+        }
+        for p in expand_user(p, users) {
+            script.push_str(&format!(
+                "for f in {p}; do [ -f \"$f\" ] && echo \"$f\"; done\n"
+            ));
+        }
+    }
+    if search.discover {
+        let names: Vec<String> = DISCOVER_NAMES
+            .iter()
+            .map(|n| format!("-name '{}'", n))
+            .collect();
+        script.push_str(&format!(
+            "find /data/misc* -maxdepth 6 -type f \\( {} \\) 2>/dev/null\n",
+            names.join(" -o ")
+        ));
+    }
+    script.push_str("true\n");
+
+    let out = run_su_cmd(&script)?;
+    let mut files: Vec<String> = Vec::new();
+    for line in out.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if !files.iter().any(|f| f == line) {
+            files.push(line.to_string());
+        }
+    }
+    Ok(files)
+}
+
+/// What "Load System" found.
+#[derive(Clone, Debug, Default)]
+pub struct SystemNetworks {
+    pub networks: Vec<WifiCred>,
+    /// Config files that parsed, in search order.
+    pub files: Vec<String>,
+    /// Users from `pm list users`; empty if it failed.
+    pub users: Vec<AndroidUser>,
+}
+
+/// Reads every system Wi‑Fi config `search` finds, for every Android user, and
+/// merges their networks with [`merge_system_networks`].
+pub fn try_read_with_su(search: &ConfigSearch) -> anyhow::Result<SystemNetworks> {
+    let users = match list_users() {
+        Ok(users) => users,
+        Err(e @ (SuError::SuNotFound | SuError::RootDenied | SuError::TimedOut(_))) => {
+            return Err(e.into());
+        }
+        Err(e) => {
+            warn!("pm list users failed, globbing user directories: {}", e);
+            Vec::new()
+        }
+    };
+    let ids: Vec<u32> = users.iter().map(|u| u.id).collect();
+    let files = find_config_files(search, (!ids.is_empty()).then_some(ids.as_slice()))?;
+    let mut read = Vec::new();
+    for p in files {
+        let bytes = match su_read(&p) {
+            Ok(bytes) => bytes,
+            // Not rooted, denied or timed out: every other file would fail the same way.
+            Err(ImportError::Root(e)) => return Err(e.into()),
+            Err(e) => {
+                warn!("Could not read {}: {}", p, e);
+                continue;
+            }
+        };
+        let Some((reader, _)) = detect_reader(&bytes) else {
+            warn!("Unrecognised config format in {}", p);
+            continue;
+        };
+        match (reader.parse)(&bytes) {
+            Ok(found) => {
+                info!("Read {} networks from {} via su", found.len(), p);
+                read.push((p, found));
+            }
+            Err(e) => warn!("{} as {}: {}", p, reader.name, e),
+        }
+    }
+    if read.is_empty() {
+        return Err(anyhow::anyhow!("No readable Wi‑Fi config via su"));
+    }
+    let files = read.iter().map(|(p, _)| p.clone()).collect();
+    Ok(SystemNetworks {
+        networks: merge_system_networks(read),
+        files,
+        users,
+    })
+}
+
+/// Tags each file's networks with the file and owning user and merges them.
+/// Within one user (or the shared store) a network seen in an earlier file wins;
+/// see [`WifiCred::network_key`]. An app suggestion is dropped when any store
+/// saves the same network, whatever its user: saved networks mostly live in the
+/// shared store while suggestions are kept per user.
+fn merge_system_networks(read: Vec<(String, Vec<WifiCred>)>) -> Vec<WifiCred> {
+    let mut seen = HashSet::new();
+    let mut saved = HashSet::new();
+    let mut networks = Vec::new();
+    let mut suggestions = Vec::new();
+    for (path, found) in read {
+        let user = owning_user(&path);
+        for mut c in found {
+            c.source = Some(path.clone());
+            c.user = user;
+            if c.suggested_by.is_some() {
+                suggestions.push(c);
+            } else if seen.insert((user, c.network_key())) {
+                saved.insert(c.network_key());
+                networks.push(c);
+            }
+        }
+    }
+    for c in suggestions {
+        if !saved.contains(&c.network_key()) && seen.insert((c.user, c.network_key())) {
+            networks.push(c);
+        }
+    }
+    networks
+}
+
+#[cfg(test)]
+mod tests;
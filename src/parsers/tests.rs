@@ -0,0 +1,671 @@
+use super::*;
+
+fn cred(ssid: &str, pass: Option<&str>) -> WifiCred {
+    WifiCred {
+        ssid: ssid.into(),
+        pass: pass.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+fn ssids(creds: &[WifiCred]) -> Vec<&str> {
+    creds.iter().map(|c| c.ssid.as_str()).collect()
+}
+
+#[test]
+fn age_export_imports_with_identity() {
+    let identity = age::x25519::Identity::generate();
+    let secret = {
+        use age::secrecy::ExposeSecret;
+        identity.to_string().expose_secret().to_string()
+    };
+    let opts = ExportOptions {
+        age_recipients: vec![identity.to_public().to_string()],
+        ..Default::default()
+    };
+    let creds = vec![cred("Home", Some("hunter22")), cred("Cafe", None)];
+    let files = build_export(ExportFormat::Age, &creds, 1, &opts).unwrap();
+
+    let parsed = parse_import_bytes(
+        files[0].contents.as_bytes(),
+        ImportOptions {
+            age_identity: Some(&secret),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(ssids(&parsed.networks), ["Home", "Cafe"]);
+    assert_eq!(parsed.networks[0].pass.as_deref(), Some("hunter22"));
+
+    assert!(parse_import_bytes(files[0].contents.as_bytes(), ImportOptions::default()).is_err());
+}
+
+#[test]
+fn hex_ssids_decode_to_text_or_keep_their_bytes() {
+    assert_eq!(decode_ssid("\"Cafe\""), ("Cafe".into(), None));
+    assert_eq!(decode_ssid("436166C3A9"), ("Café".into(), None));
+    assert_eq!(
+        decode_ssid("4361FE65"),
+        ("Ca\u{FFFD}e".into(), Some("4361FE65".into()))
+    );
+}
+
+#[test]
+fn wpa_supplicant_keeps_raw_ssid_bytes() {
+    let conf = b"network={\n\tssid=\"Caf\xe9\"\n\tpsk=\"password1\"\n}\nnetwork={\n\tssid=4F6666696365\n}\n";
+    let nets = parse_import_bytes(conf, ImportOptions::default())
+        .unwrap()
+        .networks;
+    assert_eq!(nets.len(), 2);
+    assert_eq!(nets[0].ssid, "Caf\u{FFFD}");
+    assert_eq!(nets[0].ssid_hex.as_deref(), Some("436166E9"));
+    assert_eq!(nets[0].ssid_bytes(), b"Caf\xe9");
+    assert_eq!(nets[0].pass.as_deref(), Some("password1"));
+    assert_eq!(nets[1].ssid, "Office");
+    assert_eq!(nets[1].ssid_hex, None);
+}
+
+#[test]
+fn configstore_keeps_raw_ssid_bytes() {
+    let xml = b"<WifiConfigStoreData>\n<NetworkList>\n<Network>\n<WifiConfiguration>\n\
+<string name=\"ConfigKey\">&quot;Caf\xe9&quot;WPA_PSK</string>\n\
+<string name=\"SSID\">&quot;Caf\xe9&quot;</string>\n\
+<string name=\"PreSharedKey\">&quot;password1&quot;</string>\n\
+</WifiConfiguration>\n</Network>\n</NetworkList>\n</WifiConfigStoreData>\n";
+    let nets = parse_import_bytes(xml, ImportOptions::default())
+        .unwrap()
+        .networks;
+    assert_eq!(nets.len(), 1);
+    assert_eq!(nets[0].ssid_hex.as_deref(), Some("436166E9"));
+    assert_eq!(nets[0].pass.as_deref(), Some("password1"));
+    assert!(validate_for_add(&nets[0]).is_err());
+}
+
+#[test]
+fn lossless_decoding_round_trips() {
+    let bytes = b"ok \xff\xfe \xe2\x82 \xe2\x82\xac";
+    assert_eq!(raw_bytes(&decode_lossless(bytes)), bytes);
+}
+
+#[test]
+fn windows_profiles_round_trip_raw_ssids_and_skip_eap() {
+    let raw = WifiCred {
+        ssid: "Caf\u{FFFD}".into(),
+        ssid_hex: Some("436166E9".into()),
+        pass: Some("password1".into()),
+        ..Default::default()
+    };
+    let eap = WifiCred {
+        security: Some(Security::Eap),
+        ..cred("Corp", None)
+    };
+    let creds = vec![raw.clone(), eap, cred("Home", Some("hunter22"))];
+    let files = build_export(
+        ExportFormat::WindowsWlan,
+        &creds,
+        1,
+        &ExportOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(files.len(), 2);
+    assert!(files.iter().all(|f| !f.contents.contains("useOneX>true")));
+
+    let parsed = parse_wlan_profiles(&files[0].contents);
+    assert_eq!(parsed[0].ssid_hex, raw.ssid_hex);
+    assert_eq!(parsed[0].pass, raw.pass);
+    assert_eq!(parse_wlan_profiles(&files[1].contents)[0].ssid, "Home");
+
+    let only_eap = [creds[1].clone()];
+    assert!(
+        build_export(
+            ExportFormat::WindowsWlan,
+            &only_eap,
+            1,
+            &ExportOptions::default()
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn windows_profile_prefers_hex_over_name() {
+    let xml = "<WLANProfile><name>Renamed</name><SSIDConfig><SSID>\
+<hex>486F6D65</hex><name>H?me</name></SSID></SSIDConfig></WLANProfile>";
+    assert_eq!(parse_wlan_profiles(xml)[0].ssid, "Home");
+}
+
+#[test]
+fn windows_profile_file_names_are_unique() {
+    let creds = vec![
+        cred("a/b", None),
+        cred("a?b", None),
+        cred("A_B", None),
+        cred("a_b-2", None),
+    ];
+    let files = build_export(
+        ExportFormat::WindowsWlan,
+        &creds,
+        1,
+        &ExportOptions::default(),
+    )
+    .unwrap();
+    let names: Vec<&str> = files.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "Wi-Fi-a_b.xml",
+            "Wi-Fi-a_b-2.xml",
+            "Wi-Fi-A_B-3.xml",
+            "Wi-Fi-a_b-2-2.xml"
+        ]
+    );
+}
+
+fn uci(creds: &[WifiCred], uci: UciOptions) -> String {
+    let opts = ExportOptions {
+        uci,
+        ..Default::default()
+    };
+    build_export(ExportFormat::OpenWrt, creds, 1, &opts)
+        .unwrap()
+        .remove(0)
+        .contents
+}
+
+#[test]
+fn uci_export_round_trips() {
+    let creds = vec![
+        WifiCred {
+            security: Some(Security::Wpa3),
+            hidden: true,
+            ..cred("It's home", Some("pa'ss word"))
+        },
+        WifiCred {
+            security: Some(Security::Wep),
+            ..cred("Old", Some("abcde"))
+        },
+        cred("Cafe", None),
+    ];
+    let out = uci(&creds, UciOptions::default());
+    assert!(out.contains("\toption device 'radio0'\n"));
+    assert!(out.contains("\toption network 'wwan'\n"));
+    assert_eq!(out.matches("option disabled '1'").count(), 3);
+
+    let parsed = parse_uci_wireless(&out);
+    assert_eq!(ssids(&parsed), ["It's home", "Old", "Cafe"]);
+    assert_eq!(parsed[0].pass.as_deref(), Some("pa'ss word"));
+    assert_eq!(parsed[0].security, Some(Security::Wpa3));
+    assert!(parsed[0].hidden);
+    assert_eq!(parsed[1].pass.as_deref(), Some("abcde"));
+    assert_eq!(parsed[1].security, Some(Security::Wep));
+    assert_eq!(parsed[2].pass, None);
+}
+
+#[test]
+fn uci_export_skips_eap() {
+    let corp = WifiCred {
+        security: Some(Security::Eap),
+        ..cred("Corp", None)
+    };
+    let out = uci(
+        &[corp.clone(), cred("Home", Some("hunter22"))],
+        UciOptions::default(),
+    );
+    assert!(out.contains("# Skipped 802.1X (EAP) network \"Corp\"\n"));
+    assert_eq!(ssids(&parse_uci_wireless(&out)), ["Home"]);
+
+    let opts = ExportOptions::default();
+    assert!(build_export(ExportFormat::OpenWrt, &[corp], 1, &opts).is_err());
+}
+
+#[test]
+fn uci_export_options() {
+    let out = uci(
+        &[cred("Home", Some("hunter22"))],
+        UciOptions {
+            device: "radio1".into(),
+            network: String::new(),
+            disabled: false,
+        },
+    );
+    assert!(out.contains("\toption device 'radio1'\n"));
+    assert!(!out.contains("option network"));
+    assert!(!out.contains("disabled"));
+}
+
+#[test]
+fn csv_round_trips_awkward_ssids() {
+    let creds = vec![
+        cred("Cafe, Bar", Some("pa,ss\"word")),
+        cred("\"Quoted\"", Some("semi;colon")),
+        cred("Two\nlines", None),
+        cred("Tab\there", Some("x\r\ny")),
+    ];
+    for delimiter in CSV_DELIMITERS {
+        let csv = build_csv(
+            &creds,
+            &CsvOptions {
+                delimiter,
+                ..Default::default()
+            },
+        );
+        let parsed = parse_import_bytes(csv.as_bytes(), ImportOptions::default())
+            .unwrap()
+            .networks;
+        assert_eq!(ssids(&parsed), ssids(&creds), "delimiter {delimiter:?}");
+        let passes: Vec<_> = parsed.iter().map(|c| c.pass.as_deref()).collect();
+        assert_eq!(
+            passes,
+            [
+                Some("pa,ss\"word"),
+                Some("semi;colon"),
+                None,
+                Some("x\r\ny")
+            ]
+        );
+    }
+}
+
+#[test]
+fn csv_delimiter_ignores_quoted_text() {
+    // The first physical line has more commas than semicolons, all quoted.
+    let text = "\"a,b,c\";pass1234\n\"d,e\";pass5678\nf;pass9012\n";
+    let mapping = detect_csv_mapping(text).unwrap();
+    assert_eq!(mapping.delimiter, ';');
+    assert_eq!(ssids(&parse_csv(text, &mapping)), ["a,b,c", "d,e", "f"]);
+
+    // A quoted line break in the first record.
+    let text = "\"Two\nlines\",pass1234\nother,pass5678\n";
+    let mapping = detect_csv_mapping(text).unwrap();
+    assert_eq!(mapping.delimiter, ',');
+    assert_eq!(ssids(&parse_csv(text, &mapping)), ["Two\nlines", "other"]);
+}
+
+#[test]
+fn csv_columns_can_be_remapped() {
+    let text = "Name,Key,Comment\nHome,hunter22,upstairs\n";
+    let mapping = detect_csv_mapping(text).unwrap();
+    assert_eq!(mapping.ssid, 0);
+    assert_eq!(mapping.pass, Some(1));
+    assert_eq!(csv_columns(text, &mapping), ["Name", "Key", "Comment"]);
+
+    let swapped = CsvMapping {
+        ssid: 2,
+        notes: Some(0),
+        ..mapping.clone()
+    };
+    let parsed = parse_import_bytes(
+        text.as_bytes(),
+        ImportOptions {
+            csv_mapping: Some(&swapped),
+            ..Default::default()
+        },
+    )
+    .unwrap()
+    .networks;
+    assert_eq!(ssids(&parsed), ["upstairs"]);
+    assert_eq!(parsed[0].notes.as_deref(), Some("Home"));
+
+    let headerless = CsvMapping {
+        has_header: false,
+        ..mapping
+    };
+    assert_eq!(
+        csv_columns(text, &headerless),
+        ["Column 1", "Column 2", "Column 3"]
+    );
+}
+
+#[test]
+fn keepass_entries_follow_group_nesting() {
+    let entry = |title: &str| {
+        format!(
+            "<Entry><String><Key>Title</Key><Value>{title}</Value></String>\
+             <String><Key>Password</Key><Value>pw-{title}</Value></String></Entry>"
+        )
+    };
+    let xml = format!(
+        "<KeePassFile><Root><Group><Name>Root</Name>\
+         <Group><Name>Wi-Fi</Name>\
+         <Group><Name>Office</Name>{}</Group>{}</Group>\
+         <Group><Name>Email</Name>{}</Group>{}\
+         </Group></Root></KeePassFile>",
+        entry("Nested"),
+        entry("AfterSubgroup"),
+        entry("Mail"),
+        entry("RootLevel"),
+    );
+    let parsed = parse_keepass_xml(&xml);
+    assert_eq!(ssids(&parsed), ["Nested", "AfterSubgroup"]);
+    assert_eq!(parsed[1].pass.as_deref(), Some("pw-AfterSubgroup"));
+}
+
+#[test]
+fn keepass_export_round_trips() {
+    let creds = vec![
+        WifiCred {
+            security: Some(Security::Wpa3),
+            hidden: true,
+            notes: Some("upstairs".into()),
+            ..cred("Home <5G>", Some("a&b"))
+        },
+        WifiCred {
+            security: Some(Security::Open),
+            ..cred("Cafe", None)
+        },
+    ];
+    let parsed = parse_keepass_xml(&build_keepass_xml(&creds));
+    assert_eq!(parsed, creds);
+}
+
+#[test]
+fn envelope_checksum_covers_the_networks_as_written() {
+    let creds = vec![cred("Home", Some("secret123")), cred("Cafe", None)];
+    let json = build_json(&creds, 1_760_000_000, None);
+    assert_eq!(parse_imported_json(&json).unwrap(), creds);
+
+    // Reformatting the file keeps the checksum valid.
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let compact = serde_json::to_string(&value).unwrap();
+    assert_eq!(parse_imported_json(&compact).unwrap(), creds);
+
+    // A field this version ignores is still covered.
+    value["networks"][0]["future_field"] = "x".into();
+    let edited = serde_json::to_string(&value).unwrap();
+    assert!(matches!(
+        parse_imported_json(&edited),
+        Err(ImportError::ChecksumMismatch)
+    ));
+}
+
+#[test]
+fn network_key_is_exact_ssid_and_security() {
+    let home = cred("Home", Some("secret123"));
+    assert_ne!(
+        home.network_key(),
+        cred("home", Some("secret123")).network_key()
+    );
+    let open = WifiCred {
+        security: Some(Security::Open),
+        ..cred("Home", None)
+    };
+    assert_ne!(home.network_key(), open.network_key());
+    let pass_changed = cred("Home", Some("other"));
+    assert_eq!(home.network_key(), pass_changed.network_key());
+}
+
+/// Every `<UUID>`/`PayloadUUID` value or Bitwarden item id in an export.
+fn export_ids(text: &str) -> Vec<String> {
+    if let Ok(v) = serde_json::from_str::<serde_json::Value>(text) {
+        return v["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|i| i["id"].as_str().unwrap().to_string())
+            .collect();
+    }
+    let mut ids = Vec::new();
+    for (open, close) in [
+        ("<key>PayloadUUID</key>", "</string>"),
+        ("<UUID>", "</UUID>"),
+    ] {
+        for part in text.split(open).skip(1) {
+            let value = &part[..part.find(close).unwrap()];
+            ids.push(value.trim().trim_start_matches("<string>").to_string());
+        }
+    }
+    ids
+}
+
+#[test]
+fn same_ssid_networks_get_distinct_export_ids() {
+    let creds = vec![
+        cred("Cafe", Some("secret123")),
+        WifiCred {
+            security: Some(Security::Open),
+            ..cred("Cafe", None)
+        },
+    ];
+    for export in [
+        build_mobileconfig(&creds),
+        build_bitwarden_json(&creds),
+        build_keepass_xml(&creds),
+    ] {
+        let ids = export_ids(&export);
+        let unique: HashSet<&String> = ids.iter().collect();
+        assert!(ids.len() >= creds.len(), "{}", export);
+        assert_eq!(unique.len(), ids.len(), "{}", export);
+    }
+}
+
+type Scores = &'static [(&'static str, u8)];
+
+/// Every reader's nonzero score for each file in `tests/fixtures/detect`, and
+/// how many networks the winner reads. The first reader listed must win.
+const DETECT_CORPUS: &[(&str, Scores, usize)] = &[
+    ("export-v1.json", &[("json", 95)], 2),
+    ("bare-array.json", &[("json", 70)], 2),
+    ("WifiConfigStore.xml", &[("configstore", 95)], 2),
+    (
+        "WifiConfigStoreNetworkSuggestions.xml",
+        &[("suggestions", 97), ("configstore", 95)],
+        1,
+    ),
+    ("wpa_supplicant.conf", &[("wpa_supplicant", 85)], 2),
+    ("networks.mobileconfig", &[("mobileconfig", 95)], 2),
+    ("wireless", &[("uci", 90)], 2),
+    ("Wi-Fi-Home.xml", &[("wlan", 95)], 1),
+    ("keepass.xml", &[("keepass", 95)], 2),
+    ("bitwarden.json", &[("bitwarden", 90), ("json", 50)], 2),
+    ("1password.csv", &[("1password", 85), ("csv", 40)], 2),
+    ("wifi-uris.txt", &[("wifi_uri", 90), ("csv", 40)], 2),
+    (
+        "Home.nmconnection",
+        &[("nmconnection", 90), ("json", 50)],
+        1,
+    ),
+    ("networks.csv", &[("csv", 40)], 2),
+    ("semicolons.csv", &[("csv", 40)], 2),
+    ("notes.txt", &[], 0),
+];
+
+#[test]
+fn detection_corpus_scores() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/detect");
+    let mut listed: Vec<&str> = DETECT_CORPUS.iter().map(|(f, _, _)| *f).collect();
+    let mut on_disk: Vec<String> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .filter(|f| f != "README.md")
+        .collect();
+    listed.sort();
+    on_disk.sort();
+    assert_eq!(listed, on_disk, "every fixture needs expected scores");
+
+    for (file, expected, count) in DETECT_CORPUS {
+        let bytes = std::fs::read(format!("{dir}/{file}")).unwrap();
+        let mut scores: Vec<(&str, u8)> = IMPORT_READERS
+            .iter()
+            .map(|r| (r.id, (r.detect)(&bytes)))
+            .filter(|(_, s)| *s > 0)
+            .collect();
+        scores.sort_by_key(|&(_, s)| std::cmp::Reverse(s));
+        assert_eq!(&scores, expected, "{file}");
+
+        let winner = detect_reader(&bytes).map(|(r, _)| r.id);
+        assert_eq!(winner, expected.first().map(|(id, _)| *id), "{file}");
+        match parse_import_bytes(&bytes, ImportOptions::default()) {
+            Ok(parsed) => assert_eq!(parsed.networks.len(), *count, "{file}"),
+            Err(e) => assert_eq!(*count, 0, "{file}: {e}"),
+        }
+    }
+}
+
+/// Deterministic xorshift source for the generated-input tests below.
+struct Gen(u64);
+
+impl Gen {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// Text heavy on what breaks shell quoting; never NUL, which no shell
+    /// variable can hold.
+    fn text(&mut self, max_chars: usize) -> String {
+        const PIECES: &[&str] = &[
+            "a", "Z", "7", " ", "'", "\"", "$", "`", "\\", "\n", "\t", "-", "--", ";", "&", "|",
+            "*", "?", "!", "#", "(", ")", "{", "}", "%", "$(id)", "x", "é", "😀", "\u{7f}",
+        ];
+        let len = self.below(max_chars + 1);
+        (0..len).map(|_| PIECES[self.below(PIECES.len())]).collect()
+    }
+}
+
+/// Runs `setup` and `add` with `cmd` stubbed, returning the arguments `cmd`
+/// received.
+fn run_add_script(setup: &str, add: &str) -> Vec<String> {
+    let script = format!("cmd() {{ printf '%s\\0' \"$@\"; }}\n{setup}\n{add}\n");
+    let out = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(script)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let out = String::from_utf8(out.stdout).unwrap();
+    out.split_terminator('\0').map(str::to_string).collect()
+}
+
+#[test]
+fn shell_assign_decodes_to_the_original() {
+    let mut g = Gen(0x5eed_0046);
+    for _ in 0..200 {
+        let value = g.text(40);
+        let script = format!("{}\nprintf '%s' \"$v\"", shell_assign("v", &value));
+        let out = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8(out.stdout).unwrap(), value);
+    }
+}
+
+#[test]
+fn add_network_script_passes_credentials_verbatim() {
+    let mut g = Gen(0xadd_0046);
+    let mut checked = 0;
+    while checked < 150 {
+        let security = [
+            None,
+            Some(Security::Wpa2),
+            Some(Security::Wpa3),
+            Some(Security::Open),
+        ][g.below(4)];
+        let pass = match security {
+            Some(Security::Open) => None,
+            Some(Security::Wpa3) => Some(g.text(80)),
+            _ => Some(g.text(63)),
+        };
+        let cred = WifiCred {
+            ssid: g.text(16),
+            pass,
+            security,
+            ..Default::default()
+        };
+        if validate_for_add(&cred).is_err() {
+            continue;
+        }
+        checked += 1;
+        let (setup, add) = add_network_shell(&cred);
+        let mut expected = vec![
+            "wifi".to_string(),
+            "add-network".into(),
+            cred.ssid.clone(),
+            add_network_type(&cred).into(),
+        ];
+        expected.extend(cred.pass.clone().filter(|p| !p.is_empty()));
+        assert_eq!(run_add_script(&setup, &add), expected, "{cred:?}");
+    }
+}
+
+#[test]
+fn add_validation_follows_security() {
+    let check = |security, pass: &str| {
+        validate_for_add(&WifiCred {
+            security,
+            ..cred("Net", (!pass.is_empty()).then_some(pass))
+        })
+    };
+    assert!(check(Some(Security::Wep), "abcde").is_ok());
+    assert!(check(Some(Security::Wep), "0123456789abc").is_ok());
+    // `cmd wifi` would quote a hex key and save it as text.
+    assert!(
+        check(Some(Security::Wep), "0123456789")
+            .unwrap_err()
+            .contains("Hex WEP")
+    );
+    assert!(
+        check(Some(Security::Wep), "0123456789abcdef0123456789")
+            .unwrap_err()
+            .contains("Hex WEP")
+    );
+    assert!(check(Some(Security::Wep), "abcdef").is_err());
+    assert!(check(Some(Security::Wep), "012345678g").is_err());
+
+    let long = "p".repeat(100);
+    assert!(check(Some(Security::Wpa3), &long).is_ok());
+    assert!(check(Some(Security::Wpa3), "short").is_ok());
+    assert!(check(Some(Security::Wpa3), "").is_err());
+    assert!(check(Some(Security::Wpa2), &long).is_err());
+    assert!(check(None, &long).is_err());
+    assert!(check(Some(Security::Wpa2), "short").is_err());
+    assert!(
+        check(Some(Security::Wpa2), &"ab".repeat(32))
+            .unwrap_err()
+            .contains("hex PSK")
+    );
+    assert!(check(Some(Security::Wpa2), &"ab".repeat(31)).is_ok());
+
+    assert!(check(Some(Security::Open), "").is_ok());
+    assert!(check(Some(Security::Open), "password1").is_err());
+    assert!(check(Some(Security::Eap), "password1").is_err());
+}
+
+#[test]
+fn saved_network_in_the_shared_store_hides_the_same_suggestion() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/detect");
+    let xml = std::fs::read(format!("{dir}/WifiConfigStoreNetworkSuggestions.xml")).unwrap();
+    let suggested = parse_network_suggestions_xml(&decode_lossless(&xml));
+    assert_eq!(ssids(&suggested), ["Carrier Hotspot"]);
+    let saved = WifiCred {
+        security: Some(Security::Wpa2),
+        ..cred("Carrier Hotspot", Some("hotspot-pass"))
+    };
+    let shared = "/data/misc/apexdata/com.android.wifi/WifiConfigStore.xml";
+    let user0 = "/data/misc_ce/0/apexdata/com.android.wifi/WifiConfigStoreNetworkSuggestions.xml";
+
+    // Suggestions read first still lose to the saved copy.
+    let merged = merge_system_networks(vec![
+        (user0.into(), suggested.clone()),
+        (shared.into(), vec![saved.clone()]),
+    ]);
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].suggested_by, None);
+    assert_eq!(merged[0].user, None);
+
+    // Without a saved copy the suggestion stays, tagged with its user.
+    let merged = merge_system_networks(vec![(user0.into(), suggested)]);
+    assert_eq!(merged[0].user, Some(0));
+    assert!(merged[0].suggested_by.is_some());
+}
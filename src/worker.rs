@@ -0,0 +1,184 @@
+//! Runs slow root operations (su prompts, bulk `cmd wifi` imports) on a
+//! background thread. `Signal`s are UI-thread only, so the thread queues
+//! [`Event`]s which `app()` drains on the next frame.
+
+use crate::app::ExportTarget;
+use crate::diagnostics::Check;
+use crate::error::{ImportError, SuError};
+use crate::parsers::{ExportFile, ImportSummary, ParsedImport, SystemNetworks};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Task {
+    LoadSystem,
+    LoadFile,
+    ImportAll,
+    AddNetwork,
+    Diagnostics,
+    Encrypt,
+    Decrypt,
+    ReadDocuments,
+    WriteDocument,
+}
+
+impl Task {
+    pub fn label(self) -> &'static str {
+        match self {
+            Task::LoadSystem => "Loading system networks",
+            Task::LoadFile => "Reading import file",
+            Task::ImportAll => "Adding networks",
+            Task::AddNetwork => "Adding network",
+            Task::Diagnostics => "Running diagnostics",
+            Task::Encrypt => "Encrypting export",
+            Task::Decrypt => "Decrypting import",
+            Task::ReadDocuments => "Reading documents",
+            Task::WriteDocument => "Saving",
+        }
+    }
+}
+
+pub enum Outcome {
+    System(anyhow::Result<SystemNetworks>),
+    /// File contents, or the first read error other than "not found"; `Ok(None)`
+    /// when no candidate exists.
+    File(Result<Option<Vec<u8>>, ImportError>),
+    Imported(Result<ImportSummary, SuError>),
+    /// One network added with the row's "+", by SSID.
+    Added(String, anyhow::Result<String>),
+    Diagnostics(Vec<Check>),
+    /// A passphrase-encrypted export, and where it goes.
+    Encrypted(anyhow::Result<Vec<ExportFile>>, ExportTarget),
+    /// An encrypted import, decrypted and parsed.
+    Decrypted(Result<ParsedImport, ImportError>),
+    /// Picked or shared documents by URI, in order.
+    Documents(Vec<(String, anyhow::Result<Vec<u8>>)>),
+    /// One "Save as" file written to the URI the user picked.
+    DocumentWritten(String, anyhow::Result<()>),
+}
+
+pub enum Event {
+    /// One network of a bulk import finished; `error` is `None` on success.
+    NetworkAdded {
+        /// Index into the loaded list, which may hold networks the import skips.
+        row: usize,
+        index: usize,
+        total: usize,
+        ssid: String,
+        error: Option<String>,
+    },
+    Done(Outcome),
+    Cancelled(Task),
+}
+
+struct Running {
+    task: Task,
+    cancel: Arc<AtomicBool>,
+}
+
+static RUNNING: Mutex<Option<Running>> = Mutex::new(None);
+static EVENTS: Mutex<Vec<Event>> = Mutex::new(Vec::new());
+
+/// Handed to the task body for progress reports and cancellation checks.
+pub struct Context {
+    cancel: Arc<AtomicBool>,
+}
+
+impl Context {
+    pub fn network_added(
+        &self,
+        row: usize,
+        index: usize,
+        total: usize,
+        ssid: String,
+        error: Option<String>,
+    ) {
+        push(Event::NetworkAdded {
+            row,
+            index,
+            total,
+            ssid,
+            error,
+        });
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
+
+/// Makes the UI thread compose again so it drains the queue.
+pub fn wake_ui() {
+    repose_core::request_frame();
+    repose_platform::wake_event_loop();
+}
+
+fn push(event: Event) {
+    if let Ok(mut q) = EVENTS.lock() {
+        q.push(event);
+    }
+    wake_ui();
+}
+
+/// Starts `body` on a worker thread. Returns the task already running instead
+/// when there is one; every task shells out to su, so only one runs at a time.
+pub fn spawn<F>(task: Task, body: F) -> Result<(), Task>
+where
+    F: FnOnce(&Context) -> Outcome + Send + 'static,
+{
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let Ok(mut running) = RUNNING.lock() else {
+            return Err(task);
+        };
+        if let Some(r) = running.as_ref() {
+            return Err(r.task);
+        }
+        *running = Some(Running {
+            task,
+            cancel: cancel.clone(),
+        });
+    }
+
+    let ctx = Context { cancel };
+    let spawned = std::thread::Builder::new()
+        .name(format!("worker-{:?}", task))
+        .spawn(move || {
+            let outcome = body(&ctx);
+            if let Ok(mut running) = RUNNING.lock() {
+                *running = None;
+            }
+            push(if ctx.is_cancelled() {
+                Event::Cancelled(task)
+            } else {
+                Event::Done(outcome)
+            });
+        });
+    if spawned.is_err() {
+        if let Ok(mut running) = RUNNING.lock() {
+            *running = None;
+        }
+        return Err(task);
+    }
+    Ok(())
+}
+
+pub fn running() -> Option<Task> {
+    RUNNING.lock().ok().and_then(|r| r.as_ref().map(|r| r.task))
+}
+
+/// Asks the running task to stop at its next check; its result is discarded.
+pub fn cancel() {
+    if let Ok(running) = RUNNING.lock()
+        && let Some(r) = running.as_ref()
+    {
+        r.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+pub fn take_events() -> Vec<Event> {
+    EVENTS
+        .lock()
+        .map(|mut q| std::mem::take(&mut *q))
+        .unwrap_or_default()
+}